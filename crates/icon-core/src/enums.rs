@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Icon {
+    pub body: String,
+    /// The view box of the icon, when it differs from the collection defaults.
+    pub left: Option<isize>,
+    pub top: Option<isize>,
+    pub width: Option<usize>,
+    pub height: Option<usize>,
+    /// Whether the body is mirrored horizontally or vertically.
+    pub h_flip: Option<bool>,
+    pub v_flip: Option<bool>,
+    /// The number of quarter turns the body is rotated clockwise.
+    pub rotate: Option<u32>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IconCollection {
+    pub prefix: String,
+    pub width: Option<usize>,
+    pub height: Option<usize>,
+    pub prefixes: Option<HashMap<String, String>>,
+    pub suffixes: Option<HashMap<String, String>>,
+    /// The codepoints of the icons in the icon font of the collection, as
+    /// hexadecimal codepoints mapped to icon names.
+    pub chars: Option<HashMap<String, String>>,
+    pub last_modified: usize,
+    pub info: Collection,
+    pub icons: HashMap<String, Icon>,
+    /// Alternative names of icons, mapped to the icon they point to.
+    pub aliases: Option<HashMap<String, Alias>>,
+    /// The categories of the collection, mapped to the names of their icons.
+    pub categories: Option<HashMap<String, Vec<String>>>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Alias {
+    pub parent: String,
+    /// The view box of the alias, when it differs from the one of its parent.
+    pub left: Option<isize>,
+    pub top: Option<isize>,
+    pub width: Option<usize>,
+    pub height: Option<usize>,
+    /// Flips and quarter turns on top of those of the parent.
+    pub h_flip: Option<bool>,
+    pub v_flip: Option<bool>,
+    pub rotate: Option<u32>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Info {}
+
+/// The theme definitions of a collection. Keys are the prefix or suffix of the
+/// icon names in the theme, values are the title of the theme.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Themes {
+    pub prefixes: Option<HashMap<String, String>>,
+    pub suffixes: Option<HashMap<String, String>>,
+}
+
+impl From<&IconCollection> for Themes {
+    fn from(collection: &IconCollection) -> Self {
+        Themes {
+            prefixes: collection.prefixes.clone(),
+            suffixes: collection.suffixes.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Collection {
+    pub name: String,
+    pub total: usize,
+    pub author: Author,
+    pub license: License,
+    pub samples: Vec<String>,
+    pub height: Option<usize>,
+    pub category: Option<String>,
+    pub palette: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Author {
+    pub name: String,
+    pub url: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct License {
+    pub title: String,
+    pub spdx: String,
+    pub url: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SearchRequest {
+    pub query: String,
+    pub prefix: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SearchResponse {
+    pub icons: Vec<String>,
+    pub total: usize,
+    pub limit: usize,
+    pub start: usize,
+    pub collections: HashMap<String, Collection>,
+    pub request: SearchRequest,
+}
@@ -0,0 +1,8 @@
+//! Reads Iconify icons from the local cache of the icon CLI, without network access.
+
+pub mod enums;
+pub mod lookup;
+
+pub use lookup::{
+    close_matches, get_collection, icon_data, lookup, resolve_alias, IconData, StaticIcon,
+};
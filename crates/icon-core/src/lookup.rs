@@ -0,0 +1,438 @@
+use std::{
+    error::Error,
+    fs::File,
+    io::{BufRead, BufReader},
+    path::PathBuf,
+};
+
+use crate::enums::{Alias, IconCollection};
+
+/// The size, view box and body of a single icon.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IconData {
+    pub width: usize,
+    pub height: usize,
+    pub body: String,
+}
+
+impl IconData {
+    pub fn view_box(&self) -> String {
+        format!("0 0 {} {}", self.width, self.height)
+    }
+
+    /// Wraps the body in a standalone SVG document at the size of the icon.
+    pub fn to_svg(&self) -> String {
+        format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="{}">{}</svg>"#,
+            self.width,
+            self.height,
+            self.view_box(),
+            self.body
+        )
+    }
+}
+
+/// An icon embedded at compile time by the `icon_data!` macro of icon-macro.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StaticIcon {
+    pub width: usize,
+    pub height: usize,
+    pub view_box: &'static str,
+    pub body: &'static str,
+}
+
+/// The directory of the cache, relative to the home directory.
+pub const CACHE_DIR: &str = ".local/share/icon-rs/cache";
+
+/// The directory the collections and the list of icons are cached in.
+pub fn cache_dir() -> Result<PathBuf, Box<dyn Error>> {
+    let home = home::home_dir().ok_or("Unable to get home dir.")?;
+
+    Ok(home.join(CACHE_DIR))
+}
+
+/// Reads a collection from the cache.
+pub fn get_collection(collection_id: &str) -> Result<IconCollection, Box<dyn Error>> {
+    let path = cache_dir()?.join(format!("collections/{}.json", collection_id));
+
+    let reader = BufReader::new(File::open(path)?);
+    let result: IconCollection = serde_json::from_reader(reader)?;
+
+    Ok(result)
+}
+
+/// The most aliases followed to reach an icon, so alias cycles can't hang a lookup.
+const MAX_ALIAS_DEPTH: usize = 24;
+
+/// Finds the icon a name stands for, and the aliases followed to reach it, from
+/// the alias of the name itself to the one closest to the icon.
+fn resolve_chain<'a>(
+    collection: &'a IconCollection,
+    name: &'a str,
+) -> Option<(&'a str, Vec<&'a Alias>)> {
+    let mut name = name;
+    let mut chain = Vec::new();
+
+    for _ in 0..=MAX_ALIAS_DEPTH {
+        if collection.icons.contains_key(name) {
+            return Some((name, chain));
+        }
+        let alias = collection.aliases.as_ref()?.get(name)?;
+        chain.push(alias);
+        name = &alias.parent;
+    }
+
+    None
+}
+
+/// Finds the name of the icon a name stands for, following aliases of aliases.
+/// Returns None if neither an icon nor an alias has that name.
+pub fn resolve_alias<'a>(collection: &'a IconCollection, name: &'a str) -> Option<&'a str> {
+    resolve_chain(collection, name).map(|(name, _)| name)
+}
+
+/// Finds an icon or alias in a collection. The flips and rotations of the icon
+/// and its aliases are applied to the body, and icons with their own view box
+/// origin have their body moved to the origin.
+pub fn icon_data(collection: &IconCollection, name: &str) -> Option<IconData> {
+    let (icon_name, chain) = resolve_chain(collection, name)?;
+    let icon = &collection.icons[icon_name];
+
+    let (mut left, mut top) = (icon.left, icon.top);
+    let (mut width, mut height) = (icon.width, icon.height);
+    let mut h_flip = icon.h_flip.unwrap_or(false);
+    let mut v_flip = icon.v_flip.unwrap_or(false);
+    let mut rotate = icon.rotate.unwrap_or(0);
+
+    // Aliases closer to the name override the view box of their parent, while
+    // their flips and rotations add up.
+    for alias in chain.iter().rev() {
+        left = alias.left.or(left);
+        top = alias.top.or(top);
+        width = alias.width.or(width);
+        height = alias.height.or(height);
+        h_flip ^= alias.h_flip.unwrap_or(false);
+        v_flip ^= alias.v_flip.unwrap_or(false);
+        rotate += alias.rotate.unwrap_or(0);
+    }
+
+    let mut left = left.unwrap_or(0);
+    let mut top = top.unwrap_or(0);
+    let mut width = width.or(collection.width).unwrap_or(16);
+    let mut height = height.or(collection.height).unwrap_or(16);
+
+    // The same transforms as Iconify uses: flipping both ways is half a turn.
+    let mut transforms = Vec::new();
+    if h_flip && v_flip {
+        rotate += 2;
+    } else if h_flip || v_flip {
+        let (x, y, scale) = match h_flip {
+            true => (width as isize + left, -top, "-1 1"),
+            false => (-left, height as isize + top, "1 -1"),
+        };
+        transforms.push(format!("translate({} {}) scale({})", x, y, scale));
+        (left, top) = (0, 0);
+    }
+
+    let center_x = width as f64 / 2.0 + left as f64;
+    let center_y = height as f64 / 2.0 + top as f64;
+    match rotate % 4 {
+        1 => transforms.insert(0, format!("rotate(90 {0} {0})", center_y)),
+        2 => transforms.insert(0, format!("rotate(180 {} {})", center_x, center_y)),
+        3 => transforms.insert(0, format!("rotate(-90 {0} {0})", center_x)),
+        _ => {}
+    }
+    if rotate % 2 == 1 {
+        (left, top) = (top, left);
+        (width, height) = (height, width);
+    }
+
+    let mut body = icon.body.clone();
+    if !transforms.is_empty() {
+        body = format!(r#"<g transform="{}">{}</g>"#, transforms.join(" "), body);
+    }
+    if (left, top) != (0, 0) {
+        body = format!(
+            r#"<g transform="translate({} {})">{}</g>"#,
+            -left, -top, body
+        );
+    }
+
+    Some(IconData {
+        width,
+        height,
+        body,
+    })
+}
+
+/// Looks up an icon such as `mdi:home` in the cached collections.
+pub fn lookup(icon_identifier: &str) -> Result<IconData, Box<dyn Error>> {
+    let (collection_id, name) = icon_identifier
+        .split_once(':')
+        .ok_or(format!("Invalid icon identifier '{}'.", icon_identifier))?;
+
+    let collection = get_collection(collection_id).map_err(|_| {
+        format!(
+            "The '{}' collection is not cached, run `icon cache fetch` and `icon cache generate` first.",
+            collection_id
+        )
+    })?;
+
+    icon_data(&collection, name).ok_or(format!("Unknown icon '{}'.", icon_identifier).into())
+}
+
+/// Finds the cached icons with identifiers closest to the given one, for
+/// suggestions when it doesn't exist.
+pub fn close_matches(icon_identifier: &str, count: usize) -> Vec<String> {
+    let Ok(file) = cache_dir().and_then(|dir| Ok(File::open(dir.join("icons.txt"))?)) else {
+        return vec![];
+    };
+
+    closest_icons(
+        icon_identifier,
+        BufReader::new(file).lines().map_while(Result::ok),
+        count,
+    )
+}
+
+/// Picks the icons with identifiers closest to the given one, closest first.
+/// Icons that differ in more than a third of the identifier are left out.
+fn closest_icons(
+    icon_identifier: &str,
+    icons: impl Iterator<Item = String>,
+    count: usize,
+) -> Vec<String> {
+    let max_distance = (icon_identifier.len() / 3).max(2);
+
+    let mut matches: Vec<(usize, String)> = icons
+        .filter_map(|icon| {
+            let distance = edit_distance(icon_identifier, &icon);
+            (distance <= max_distance).then_some((distance, icon))
+        })
+        .collect();
+    matches.sort();
+
+    matches
+        .into_iter()
+        .take(count)
+        .map(|(_, icon)| icon)
+        .collect()
+}
+
+/// The Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != *b);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn collection() -> IconCollection {
+        serde_json::from_value(json!({
+            "prefix": "test",
+            "width": 24,
+            "height": 24,
+            "lastModified": 0,
+            "info": {
+                "name": "Test",
+                "total": 2,
+                "author": { "name": "Tester" },
+                "license": { "title": "MIT", "spdx": "MIT" },
+                "samples": [],
+                "palette": false
+            },
+            "icons": {
+                "arrow-right": { "body": "<path d=\"M4 11h12\"/>" },
+                "wide": { "body": "<path d=\"M0 0h20\"/>", "width": 20, "height": 10 },
+                "offset": { "body": "<path/>", "left": 2, "top": 4 }
+            },
+            "aliases": {
+                "arrow-left": { "parent": "arrow-right", "hFlip": true },
+                "arrow-left-wide": { "parent": "arrow-left", "width": 32 },
+                "arrow-right-again": { "parent": "arrow-left", "hFlip": true },
+                "arrow-down": { "parent": "arrow-right", "rotate": 1 },
+                "arrow-up": { "parent": "arrow-down", "rotate": 2 },
+                "arrow-mirrored": { "parent": "arrow-right", "hFlip": true, "vFlip": true },
+                "arrow-upside-down": { "parent": "arrow-right", "vFlip": true },
+                "wide-turned": { "parent": "wide", "rotate": 1 },
+                "offset-flipped": { "parent": "offset", "hFlip": true },
+                "loop-a": { "parent": "loop-b" },
+                "loop-b": { "parent": "loop-a" }
+            }
+        }))
+        .unwrap()
+    }
+
+    fn data(name: &str) -> (usize, usize, String) {
+        let data = icon_data(&collection(), name).unwrap();
+        (data.width, data.height, data.body)
+    }
+
+    const BODY: &str = r#"<path d="M4 11h12"/>"#;
+
+    #[test]
+    fn icons_are_returned_as_they_are() {
+        assert_eq!(data("arrow-right"), (24, 24, BODY.to_string()));
+        assert_eq!(
+            data("offset"),
+            (
+                24,
+                24,
+                r#"<g transform="translate(-2 -4)"><path/></g>"#.to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn flipped_aliases_are_mirrored() {
+        assert_eq!(
+            data("arrow-left"),
+            (
+                24,
+                24,
+                format!(r#"<g transform="translate(24 0) scale(-1 1)">{}</g>"#, BODY)
+            )
+        );
+        assert_eq!(
+            data("arrow-upside-down"),
+            (
+                24,
+                24,
+                format!(r#"<g transform="translate(0 24) scale(1 -1)">{}</g>"#, BODY)
+            )
+        );
+        // Flipping both ways is half a turn.
+        assert_eq!(
+            data("arrow-mirrored"),
+            (
+                24,
+                24,
+                format!(r#"<g transform="rotate(180 12 12)">{}</g>"#, BODY)
+            )
+        );
+        // The flip moves the view box to the origin.
+        assert_eq!(
+            data("offset-flipped"),
+            (
+                24,
+                24,
+                r#"<g transform="translate(26 -4) scale(-1 1)"><path/></g>"#.to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn rotated_aliases_are_turned() {
+        assert_eq!(
+            data("arrow-down"),
+            (
+                24,
+                24,
+                format!(r#"<g transform="rotate(90 12 12)">{}</g>"#, BODY)
+            )
+        );
+        // A quarter turn swaps the width and height.
+        assert_eq!(
+            data("wide-turned"),
+            (
+                10,
+                20,
+                r#"<g transform="rotate(90 5 5)"><path d="M0 0h20"/></g>"#.to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn aliases_of_aliases_combine_their_transforms() {
+        // A quarter turn and a half turn make three quarter turns.
+        assert_eq!(
+            data("arrow-up"),
+            (
+                24,
+                24,
+                format!(r#"<g transform="rotate(-90 12 12)">{}</g>"#, BODY)
+            )
+        );
+        // Two flips cancel out.
+        assert_eq!(data("arrow-right-again"), (24, 24, BODY.to_string()));
+        // The view box of the outer alias wins.
+        assert_eq!(
+            data("arrow-left-wide"),
+            (
+                32,
+                24,
+                format!(r#"<g transform="translate(32 0) scale(-1 1)">{}</g>"#, BODY)
+            )
+        );
+    }
+
+    #[test]
+    fn resolves_aliases_to_their_icon() {
+        let collection = collection();
+
+        assert_eq!(
+            resolve_alias(&collection, "arrow-right"),
+            Some("arrow-right")
+        );
+        assert_eq!(resolve_alias(&collection, "arrow-up"), Some("arrow-right"));
+        assert_eq!(resolve_alias(&collection, "missing"), None);
+    }
+
+    #[test]
+    fn alias_cycles_resolve_to_nothing() {
+        let collection = collection();
+
+        assert_eq!(resolve_alias(&collection, "loop-a"), None);
+        assert!(icon_data(&collection, "loop-b").is_none());
+    }
+
+    #[test]
+    fn edit_distance_counts_single_character_edits() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("home", "home"), 0);
+        assert_eq!(edit_distance("", "home"), 4);
+        assert_eq!(edit_distance("home", "hom"), 1);
+        assert_eq!(edit_distance("home", "hoome"), 1);
+        assert_eq!(edit_distance("home", "hame"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn closest_icons_are_sorted_by_distance() {
+        let icons = [
+            "mdi:home",
+            "mdi:house",
+            "mdi:homes",
+            "mdi:arrow-left",
+            "lucide:home",
+        ]
+        .map(String::from);
+
+        assert_eq!(
+            closest_icons("mdi:hom", icons.clone().into_iter(), 3),
+            vec!["mdi:home", "mdi:homes"]
+        );
+        assert_eq!(
+            closest_icons("mdi:hom", icons.clone().into_iter(), 1),
+            vec!["mdi:home"]
+        );
+        assert!(closest_icons("mdi:settings", icons.into_iter(), 3).is_empty());
+    }
+}
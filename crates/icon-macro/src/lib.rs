@@ -0,0 +1,66 @@
+//! Embeds Iconify icons at compile time, read from the local cache of the icon CLI.
+//!
+//! ```ignore
+//! const HOME: &str = icon_macro::icon!("mdi:home");
+//! const ARROW: icon_core::StaticIcon = icon_macro::icon_data!("mdi:arrow-left");
+//! ```
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, LitStr};
+
+/// The number of close matches suggested for unknown icons.
+const SUGGESTIONS: usize = 3;
+
+/// Looks up an icon, turning a failure into a compile error on the literal.
+fn lookup(identifier: &LitStr) -> Result<icon_core::IconData, syn::Error> {
+    icon_core::lookup(&identifier.value()).map_err(|e| {
+        let suggestions = icon_core::close_matches(&identifier.value(), SUGGESTIONS);
+
+        let message = if suggestions.is_empty() {
+            e.to_string()
+        } else {
+            format!("{} Did you mean {}?", e, suggestions.join(", "))
+        };
+
+        syn::Error::new(identifier.span(), message)
+    })
+}
+
+/// Expands to a `&'static str` with the standalone SVG document of an icon.
+#[proc_macro]
+pub fn icon(input: TokenStream) -> TokenStream {
+    let identifier = parse_macro_input!(input as LitStr);
+
+    match lookup(&identifier) {
+        Ok(icon) => {
+            let svg = icon.to_svg();
+            quote!(#svg).into()
+        }
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Expands to an `icon_core::StaticIcon` with the size, view box and body of an icon.
+#[proc_macro]
+pub fn icon_data(input: TokenStream) -> TokenStream {
+    let identifier = parse_macro_input!(input as LitStr);
+
+    match lookup(&identifier) {
+        Ok(icon) => {
+            let width = icon.width;
+            let height = icon.height;
+            let view_box = icon.view_box();
+            let body = icon.body;
+
+            quote!(::icon_core::StaticIcon {
+                width: #width,
+                height: #height,
+                view_box: #view_box,
+                body: #body,
+            })
+            .into()
+        }
+        Err(e) => e.to_compile_error().into(),
+    }
+}
@@ -0,0 +1,223 @@
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fs::create_dir_all,
+    path::Path,
+};
+
+use image::{DynamicImage, ImageFormat};
+use resvg::{tiny_skia::Pixmap, usvg::fontdb::Database};
+
+use crate::{
+    cli::{AppIconsArgs, Platform},
+    enums::IconCollection,
+    files::{get_icon_xml, write_file},
+    render::{pixmap_to_image, render_icon, save_pixmap, RasterOptions},
+};
+
+/// The Android density buckets and their scale relative to mdpi.
+const ANDROID_DENSITIES: [(&str, f32); 5] = [
+    ("mdpi", 1.0),
+    ("hdpi", 1.5),
+    ("xhdpi", 2.0),
+    ("xxhdpi", 3.0),
+    ("xxxhdpi", 4.0),
+];
+
+/// The size of legacy launcher icons, in dp.
+const ANDROID_LEGACY_SIZE: f32 = 48.0;
+
+/// The size of adaptive icon layers, in dp.
+const ANDROID_ADAPTIVE_SIZE: f32 = 108.0;
+
+/// The diameter of the circle in the middle of adaptive icon layers that is never
+/// masked, in dp. The icon is fitted inside it.
+const ANDROID_SAFE_ZONE: f32 = 66.0;
+
+/// The images of an iOS app icon set, as (idiom, size in points, scale).
+const IOS_IMAGES: [(&str, f32, u32); 18] = [
+    ("iphone", 20.0, 2),
+    ("iphone", 20.0, 3),
+    ("iphone", 29.0, 2),
+    ("iphone", 29.0, 3),
+    ("iphone", 40.0, 2),
+    ("iphone", 40.0, 3),
+    ("iphone", 60.0, 2),
+    ("iphone", 60.0, 3),
+    ("ipad", 20.0, 1),
+    ("ipad", 20.0, 2),
+    ("ipad", 29.0, 1),
+    ("ipad", 29.0, 2),
+    ("ipad", 40.0, 1),
+    ("ipad", 40.0, 2),
+    ("ipad", 76.0, 1),
+    ("ipad", 76.0, 2),
+    ("ipad", 83.5, 2),
+    ("ios-marketing", 1024.0, 1),
+];
+
+/// Writes the launcher icons of an Android app and the app icon set of an iOS app.
+pub fn app_icons(
+    args: &AppIconsArgs,
+    collections_cache: &mut HashMap<String, IconCollection>,
+    fontdb: &Database,
+) -> Result<(), Box<dyn Error>> {
+    let icon = get_icon_xml(&args.icon, collections_cache, None)?;
+
+    if args.platforms.contains(&Platform::Android) {
+        android(args, &icon, &args.out_dir.join("android"), fontdb)?;
+    }
+
+    if args.platforms.contains(&Platform::Ios) {
+        ios(args, &icon, &args.out_dir.join("ios"), fontdb)?;
+    }
+
+    Ok(())
+}
+
+/// Writes the legacy and round launcher icons for every density, and the layers
+/// of the adaptive icon used from Android 8 onwards.
+fn android(
+    args: &AppIconsArgs,
+    (width, height, body): &(usize, usize, String),
+    out_dir: &Path,
+    fontdb: &Database,
+) -> Result<(), Box<dyn Error>> {
+    let res = out_dir.join("res");
+
+    let legacy = RasterOptions {
+        color: args.color.clone(),
+        padding: args.padding,
+        background: Some(args.background.clone()),
+        corner_radius: args.corner_radius,
+    };
+    let round = RasterOptions {
+        corner_radius: 50.0,
+        ..legacy.clone()
+    };
+    // The background of adaptive icons is a separate layer.
+    let foreground = RasterOptions {
+        background: None,
+        corner_radius: 0.0,
+        ..legacy.clone()
+    };
+
+    for (density, scale) in ANDROID_DENSITIES {
+        let mipmap = res.join(format!("mipmap-{}", density));
+
+        let pixels = (ANDROID_LEGACY_SIZE * scale).round() as u32;
+        let padding = args.padding.to_pixels(ANDROID_LEGACY_SIZE, scale);
+
+        for (filename, options) in [
+            ("ic_launcher.png", &legacy),
+            ("ic_launcher_round.png", &round),
+        ] {
+            let pixmap = render_icon(*width, *height, body, pixels, padding, options, fontdb)?;
+            write_png(&pixmap, &mipmap.join(filename))?;
+        }
+
+        let pixels = (ANDROID_ADAPTIVE_SIZE * scale).round() as u32;
+        let padding = (ANDROID_ADAPTIVE_SIZE - ANDROID_SAFE_ZONE) / 2.0 * scale
+            + args.padding.to_pixels(ANDROID_SAFE_ZONE, scale);
+        let pixmap = render_icon(*width, *height, body, pixels, padding, &foreground, fontdb)?;
+        write_png(&pixmap, &mipmap.join("ic_launcher_foreground.png"))?;
+    }
+
+    let adaptive_icon = r#"<?xml version="1.0" encoding="utf-8"?>
+<adaptive-icon xmlns:android="http://schemas.android.com/apk/res/android">
+    <background android:drawable="@color/ic_launcher_background" />
+    <foreground android:drawable="@mipmap/ic_launcher_foreground" />
+</adaptive-icon>
+"#;
+    let anydpi = res.join("mipmap-anydpi-v26");
+    write_file(&anydpi.join("ic_launcher.xml"), adaptive_icon.as_bytes())?;
+    write_file(
+        &anydpi.join("ic_launcher_round.xml"),
+        adaptive_icon.as_bytes(),
+    )?;
+
+    let colors = format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<resources>
+    <color name="ic_launcher_background">{}</color>
+</resources>
+"#,
+        args.background
+    );
+    write_file(
+        &res.join("values/ic_launcher_background.xml"),
+        colors.as_bytes(),
+    )?;
+
+    println!("{}", res.display());
+
+    Ok(())
+}
+
+/// Writes an asset catalog app icon set. iOS masks the corners itself and doesn't
+/// allow transparency, so the icons are square and always have a background.
+fn ios(
+    args: &AppIconsArgs,
+    (width, height, body): &(usize, usize, String),
+    out_dir: &Path,
+    fontdb: &Database,
+) -> Result<(), Box<dyn Error>> {
+    let icon_set = out_dir.join("AppIcon.appiconset");
+    create_dir_all(&icon_set)?;
+
+    let options = RasterOptions {
+        color: args.color.clone(),
+        padding: args.padding,
+        background: Some(args.background.clone()),
+        corner_radius: 0.0,
+    };
+
+    let mut rendered = HashSet::new();
+    let mut images = Vec::new();
+    for (idiom, size, scale) in IOS_IMAGES {
+        let filename = format!("AppIcon-{}@{}x.png", size, scale);
+
+        // iPhone and iPad share some sizes, which only need to be rendered once.
+        if rendered.insert(filename.clone()) {
+            let pixels = (size * scale as f32).round() as u32;
+            let padding = args.padding.to_pixels(size, scale as f32);
+            let pixmap = render_icon(*width, *height, body, pixels, padding, &options, fontdb)?;
+
+            // The App Store rejects icons with an alpha channel, even if it is opaque.
+            DynamicImage::ImageRgba8(pixmap_to_image(&pixmap))
+                .to_rgb8()
+                .save_with_format(icon_set.join(&filename), ImageFormat::Png)?;
+        }
+
+        images.push(serde_json::json!({
+            "idiom": idiom,
+            "size": format!("{0}x{0}", size),
+            "scale": format!("{}x", scale),
+            "filename": filename,
+        }));
+    }
+
+    let contents = serde_json::json!({
+        "images": images,
+        "info": {
+            "author": "xcode",
+            "version": 1,
+        },
+    });
+    write_file(
+        &icon_set.join("Contents.json"),
+        serde_json::to_string_pretty(&contents)?.as_bytes(),
+    )?;
+
+    println!("{}", icon_set.display());
+
+    Ok(())
+}
+
+fn write_png(pixmap: &Pixmap, path: &Path) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)?;
+    }
+
+    save_pixmap(pixmap, path, ImageFormat::Png)
+}
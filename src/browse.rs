@@ -6,21 +6,32 @@ use std::{
 };
 
 use crate::{
-    config::read_config_file,
-    files::{get_collection, get_icon_xml, preview, query},
+    config::{get_search_api, get_template_name, Config},
+    files::{fetch_api_icons, get_icon_xml, preview},
+    history::{append_history, read_history, HistoryRecall},
+    output::{Highlighter, HIGHLIGHT_COLOR},
+    parser::is_filter_key,
+    search::{page, page_summary, search, THEMES},
+    show::print_summary,
+    similar::{find_similar, SIMILAR_RESULTS},
+    template::render_template,
 };
 use arboard::Clipboard;
 use chrono::{DateTime, TimeDelta, Utc};
 use crossterm::{
     cursor::{self, MoveTo},
     event::{poll, read, Event, KeyCode, KeyModifiers},
-    style::{Color, Print, SetBackgroundColor, SetForegroundColor},
+    style::{Color, Print, PrintStyledContent, SetBackgroundColor, SetForegroundColor, Stylize},
     terminal::{self, size, Clear, ClearType},
     QueueableCommand,
 };
+use log::{log_enabled, Level};
 use resvg::usvg::fontdb::Database;
 
-use crate::{cli::Cli, enums::IconCollection};
+use crate::{
+    cli::{BrowseArgs, QueryArgs},
+    enums::IconCollection,
+};
 
 enum Direction {
     Up,
@@ -29,19 +40,28 @@ enum Direction {
     Right,
 }
 
+/// The results of the current search, and the page of them shown on the screen.
+/// When searching remotely, `api` is where the icons of the results come from.
+struct SearchResults {
+    all: Vec<String>,
+    page_offset: usize,
+    page: Vec<String>,
+    api: Option<String>,
+}
+
 struct Message {
     message: String,
     color: Color,
     delete_at: DateTime<Utc>,
 }
 
-fn do_move<'a>(
+fn do_move(
     direction: Direction,
-    selected_index: &'a mut u16,
+    selected_index: &mut u16,
     previously_selected_index: &mut Option<u16>,
     items: u16,
     cols: &u16,
-) -> () {
+) {
     let items_per_row = (cols - 4) / 8;
 
     match direction {
@@ -72,15 +92,49 @@ fn do_move<'a>(
     }
 }
 
-fn render_query<'a>(
+/// Prints the icon identifier with the parts matched by the search highlighted.
+fn print_highlighted(
+    stdout: &mut Stdout,
+    icon: &str,
+    highlighter: &Highlighter,
+) -> Result<(), Box<dyn Error>> {
+    for (segment, matched) in highlighter.segments(icon) {
+        if matched {
+            stdout.queue(PrintStyledContent(segment.with(HIGHLIGHT_COLOR).bold()))?;
+        } else {
+            stdout.queue(Print(segment))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The number of icons that fit on a screen, which is also the size of a page.
+fn page_size() -> Result<usize, Box<dyn Error>> {
+    let (cols, rows) = size()?;
+    let icons_per_row = (cols as usize - 4) / 8;
+
+    Ok((((rows as usize) - 6) / 4) * icons_per_row)
+}
+
+fn render_query(
     stdout: &mut Stdout,
-    query_results: &mut Vec<String>,
-    selected_index: &'a mut u16,
+    results: &mut SearchResults,
+    highlighter: &Highlighter,
+    selected_index: &mut u16,
     previously_selected_index: &mut Option<u16>,
     collections_cache: &mut HashMap<String, IconCollection>,
     fontdb: &mut Database,
 ) -> Result<(), Box<dyn Error>> {
-    if query_results.is_empty() {
+    let api = results.api.as_deref();
+    let max_icons = page_size()?.max(1);
+
+    // Keep the page aligned to the screen, also after the terminal is resized.
+    results.page_offset =
+        results.page_offset.min(results.all.len().saturating_sub(1)) / max_icons * max_icons;
+    results.page = page(&results.all, results.page_offset, Some(max_icons)).to_vec();
+
+    if results.page.is_empty() {
         *selected_index = 0;
         *previously_selected_index = None;
         stdout.queue(Clear(ClearType::All))?;
@@ -88,51 +142,52 @@ fn render_query<'a>(
         Ok(())
     } else {
         let (cols, rows) = size()?;
-        let icons_per_row = (cols as usize - 4) / 8;
-        let max_icons = (((rows as usize) - 6) / 4) * icons_per_row;
-
-        query_results.truncate(max_icons);
 
         *selected_index = 0;
         *previously_selected_index = None;
         stdout.queue(Clear(ClearType::All))?;
 
+        // Icons are fetched from the search API a page at a time.
+        if let Some(api) = api {
+            fetch_api_icons(api, &results.page, collections_cache)?;
+        }
+
         let mut row = 1;
         let mut col = 2;
 
-        for (i, r) in query_results.iter().enumerate() {
-            stdout.queue(MoveTo(col as u16, row))?;
-            preview(&r, collections_cache, fontdb)?;
+        for (i, r) in results.page.iter().enumerate() {
+            stdout.queue(MoveTo(col, row))?;
+            preview(r, collections_cache, api, fontdb)?;
 
             let i = i as u16;
             if let Some(psi) = previously_selected_index {
                 if i == *psi {
-                    stdout.queue(MoveTo(col as u16, row))?;
+                    stdout.queue(MoveTo(col, row))?;
                     stdout.queue(Clear(ClearType::UntilNewLine))?;
-                    stdout.queue(MoveTo(col as u16, row + 1))?;
+                    stdout.queue(MoveTo(col, row + 1))?;
                     stdout.queue(Clear(ClearType::UntilNewLine))?;
-                    stdout.queue(MoveTo(col as u16, row + 2))?;
+                    stdout.queue(MoveTo(col, row + 2))?;
                     stdout.queue(Clear(ClearType::UntilNewLine))?;
                     stdout.flush().unwrap();
                 }
             }
 
             if i == *selected_index {
-                stdout.queue(MoveTo(col as u16, row))?;
+                stdout.queue(MoveTo(col, row))?;
                 stdout.queue(SetForegroundColor(Color::White))?;
                 stdout.queue(SetBackgroundColor(Color::Blue))?;
-                stdout.queue(Print(format!("      ")))?;
-                stdout.queue(MoveTo(col as u16, row + 1))?;
-                stdout.queue(Print(format!("      ")))?;
-                stdout.queue(MoveTo(col as u16, row + 2))?;
-                stdout.queue(Print(format!("      ")))?;
+                stdout.queue(Print("      ".to_string()))?;
+                stdout.queue(MoveTo(col, row + 1))?;
+                stdout.queue(Print("      ".to_string()))?;
+                stdout.queue(MoveTo(col, row + 2))?;
+                stdout.queue(Print("      ".to_string()))?;
                 stdout.queue(SetForegroundColor(Color::Reset))?;
                 stdout.queue(SetBackgroundColor(Color::Reset))?;
             }
 
             stdout.queue(MoveTo(1, rows - 1))?;
             stdout.queue(Clear(ClearType::CurrentLine))?;
-            stdout.queue(Print(query_results[*selected_index as usize].clone()))?;
+            print_highlighted(stdout, &results.page[*selected_index as usize], highlighter)?;
 
             if col + 18 > cols {
                 col = 2;
@@ -146,7 +201,7 @@ fn render_query<'a>(
     }
 }
 
-fn parse_original_search_string(args: &Cli) -> Result<String, Box<dyn Error>> {
+pub fn parse_original_search_string(args: &QueryArgs) -> Result<String, Box<dyn Error>> {
     let orig_query = if let Some(q) = &args.query {
         q.to_owned()
     } else {
@@ -166,20 +221,26 @@ fn parse_original_search_string(args: &Cli) -> Result<String, Box<dyn Error>> {
     }
 }
 
+fn is_collection_id(prefix: &str) -> bool {
+    !prefix.is_empty() && prefix.chars().all(|c| c.is_alphanumeric() || c == '-')
+}
+
 fn parse_search_string(
     search_string: &str,
 ) -> Result<(Option<String>, Option<String>), Box<dyn Error>> {
-    if search_string.contains(":") {
-        let (p, q) = search_string.split_once(":").unwrap();
-
-        Ok((Some(p.to_string()), Some(q.to_string())))
-    } else {
-        Ok((None, Some(search_string.to_string())))
+    match search_string.split_once(':') {
+        // Only a leading collection id is a prefix, not filters such as `style:stroke`.
+        Some((p, q)) if is_collection_id(p) && !is_filter_key(p) => {
+            let q = Some(q.to_string()).filter(|q| !q.is_empty());
+            Ok((Some(p.to_string()), q))
+        }
+        _ => Ok((None, Some(search_string.to_string()))),
     }
 }
 
 pub fn browse(
-    args: &Cli,
+    args: &BrowseArgs,
+    config: &Config,
     collections_cache: &mut HashMap<String, IconCollection>,
     fontdb: &mut Database,
 ) -> Result<(), Box<dyn Error>> {
@@ -189,13 +250,30 @@ pub fn browse(
     let mut quit = false;
     let mut selected: Option<String> = None;
     let mut search_mode = false;
-    let mut search_string = parse_original_search_string(&args)?;
+    let mut search_string = parse_original_search_string(&args.query)?;
     let mut previously_selected_index: Option<u16> = None;
     let mut selected_index: u16 = 0;
     let mut messages = Vec::<Message>::new();
+    let mut theme = args.query.theme.clone();
+    let mut history = HistoryRecall::new(read_history()?);
+    let mut template = get_template_name(args, config)?;
     // State END
 
-    let mut query_results = query(&args.query, &args.prefix)?;
+    let search_api = get_search_api(&args.query, config);
+
+    let mut results = SearchResults {
+        all: search(
+            &args.query.query,
+            &args.query.prefix,
+            &theme,
+            &search_api,
+            config,
+        )?,
+        page_offset: 0,
+        page: Vec::new(),
+        api: search_api.clone(),
+    };
+    let mut highlighter = Highlighter::new(&args.query.query, config)?;
 
     terminal::enable_raw_mode()?;
     let mut stdout = stdout();
@@ -205,7 +283,8 @@ pub fn browse(
 
     render_query(
         &mut stdout,
-        &mut query_results,
+        &mut results,
+        &highlighter,
         &mut selected_index,
         &mut previously_selected_index,
         collections_cache,
@@ -219,11 +298,13 @@ pub fn browse(
             match read().unwrap() {
                 Event::Resize(_cols, _rows) => {
                     let (p, q) = parse_search_string(&search_string)?;
-                    query_results = query(&q, &p)?;
+                    results.all = search(&q, &p, &theme, &search_api, config)?;
+                    highlighter = Highlighter::new(&q, config)?;
 
                     render_query(
                         &mut stdout,
-                        &mut query_results,
+                        &mut results,
+                        &highlighter,
                         &mut selected_index,
                         &mut previously_selected_index,
                         collections_cache,
@@ -231,25 +312,45 @@ pub fn browse(
                     )?;
                 }
                 Event::Key(event) => match event.code {
-                    KeyCode::Backspace => {
-                        if search_mode {
-                            search_string.pop();
-                        }
+                    KeyCode::Backspace if search_mode && history.is_reverse_searching() => {
+                        history.reverse_search_pop();
+                    }
+                    KeyCode::Backspace if search_mode => {
+                        search_string.pop();
                     }
-                    KeyCode::Enter if !search_mode => {
-                        selected = Some(query_results[selected_index as usize].clone());
+                    KeyCode::Enter if !search_mode && !results.page.is_empty() => {
+                        selected = Some(results.page[selected_index as usize].clone());
                         quit = true;
                     }
                     KeyCode::Enter if search_mode => {
                         search_mode = false;
                         stdout.queue(cursor::Hide)?;
 
+                        if let Some(recalled) = history.end_reverse_search() {
+                            search_string = recalled;
+                        }
+                        append_history(&search_string)?;
+                        history.push(&search_string);
+
                         let (p, q) = parse_search_string(&search_string)?;
-                        query_results = query(&q, &p)?;
+                        let mut no_results_message = "No icons matching search string.".to_string();
+                        // Mistakes like unknown saved searches shouldn't end the session.
+                        match search(&q, &p, &theme, &search_api, config) {
+                            Ok(found) => {
+                                results.all = found;
+                                results.page_offset = 0;
+                                highlighter = Highlighter::new(&q, config)?;
+                            }
+                            Err(e) => {
+                                results.all = vec![];
+                                results.page_offset = 0;
+                                no_results_message = e.to_string();
+                            }
+                        }
 
-                        if query_results.len() == 0 {
+                        if results.all.is_empty() {
                             messages.push(Message {
-                                message: "No icons matching search string.".to_string(),
+                                message: no_results_message,
                                 color: Color::Yellow,
                                 delete_at: chrono::Utc::now()
                                     .checked_add_signed(TimeDelta::seconds(2))
@@ -261,13 +362,17 @@ pub fn browse(
 
                         render_query(
                             &mut stdout,
-                            &mut query_results,
+                            &mut results,
+                            &highlighter,
                             &mut selected_index,
                             &mut previously_selected_index,
                             collections_cache,
                             fontdb,
                         )?;
                     }
+                    KeyCode::Esc if search_mode && history.is_reverse_searching() => {
+                        history.end_reverse_search();
+                    }
                     KeyCode::Esc if search_mode => {
                         search_mode = false;
                         stdout.queue(cursor::Hide)?;
@@ -275,12 +380,22 @@ pub fn browse(
                     KeyCode::Esc if !search_mode => {
                         quit = true;
                     }
+                    KeyCode::Up if search_mode => {
+                        if let Some(recalled) = history.previous(&search_string) {
+                            search_string = recalled;
+                        }
+                    }
+                    KeyCode::Down if search_mode => {
+                        if let Some(recalled) = history.next() {
+                            search_string = recalled;
+                        }
+                    }
                     KeyCode::Up => {
                         do_move(
                             Direction::Up,
                             &mut selected_index,
                             &mut previously_selected_index,
-                            query_results.len() as u16,
+                            results.page.len() as u16,
                             &cols,
                         );
                     }
@@ -289,7 +404,7 @@ pub fn browse(
                             Direction::Down,
                             &mut selected_index,
                             &mut previously_selected_index,
-                            query_results.len() as u16,
+                            results.page.len() as u16,
                             &cols,
                         );
                     }
@@ -298,7 +413,7 @@ pub fn browse(
                             Direction::Left,
                             &mut selected_index,
                             &mut previously_selected_index,
-                            query_results.len() as u16,
+                            results.page.len() as u16,
                             &cols,
                         );
                     }
@@ -307,10 +422,18 @@ pub fn browse(
                             Direction::Right,
                             &mut selected_index,
                             &mut previously_selected_index,
-                            query_results.len() as u16,
+                            results.page.len() as u16,
                             &cols,
                         );
                     }
+                    KeyCode::Char('r')
+                        if search_mode && event.modifiers.contains(KeyModifiers::CONTROL) =>
+                    {
+                        history.reverse_search();
+                    }
+                    KeyCode::Char(c) if search_mode && history.is_reverse_searching() => {
+                        history.reverse_search_push(c);
+                    }
                     KeyCode::Char(c) if search_mode => {
                         search_string.push(c);
                     }
@@ -320,7 +443,7 @@ pub fn browse(
                                 Direction::Down,
                                 &mut selected_index,
                                 &mut previously_selected_index,
-                                query_results.len() as u16,
+                                results.page.len() as u16,
                                 &cols,
                             );
                         }
@@ -329,7 +452,7 @@ pub fn browse(
                                 Direction::Up,
                                 &mut selected_index,
                                 &mut previously_selected_index,
-                                query_results.len() as u16,
+                                results.page.len() as u16,
                                 &cols,
                             );
                         }
@@ -338,7 +461,7 @@ pub fn browse(
                                 Direction::Right,
                                 &mut selected_index,
                                 &mut previously_selected_index,
-                                query_results.len() as u16,
+                                results.page.len() as u16,
                                 &cols,
                             );
                         }
@@ -347,11 +470,40 @@ pub fn browse(
                                 Direction::Left,
                                 &mut selected_index,
                                 &mut previously_selected_index,
-                                query_results.len() as u16,
+                                results.page.len() as u16,
                                 &cols,
                             );
                         }
                     }
+                    KeyCode::PageDown | KeyCode::Char(']') => {
+                        let page_size = page_size()?.max(1);
+
+                        if results.page_offset + page_size < results.all.len() {
+                            results.page_offset += page_size;
+                            render_query(
+                                &mut stdout,
+                                &mut results,
+                                &highlighter,
+                                &mut selected_index,
+                                &mut previously_selected_index,
+                                collections_cache,
+                                fontdb,
+                            )?;
+                        }
+                    }
+                    KeyCode::PageUp | KeyCode::Char('[') if results.page_offset > 0 => {
+                        results.page_offset =
+                            results.page_offset.saturating_sub(page_size()?.max(1));
+                        render_query(
+                            &mut stdout,
+                            &mut results,
+                            &highlighter,
+                            &mut selected_index,
+                            &mut previously_selected_index,
+                            collections_cache,
+                            fontdb,
+                        )?;
+                    }
                     KeyCode::Char('q') => {
                         quit = true;
                     }
@@ -360,7 +512,7 @@ pub fn browse(
                         stdout.queue(cursor::Show)?;
                     }
                     KeyCode::Char('c') => {
-                        let id = query_results[selected_index as usize].clone();
+                        let id = results.page[selected_index as usize].clone();
                         clipboard.set_text(id.clone())?;
 
                         messages.push(Message {
@@ -372,24 +524,41 @@ pub fn browse(
                         });
                     }
                     KeyCode::Char('C') => {
-                        let id = query_results[selected_index as usize].clone();
-
-                        let config = read_config_file()?;
-
-                        match config.custom_output {
-                            Some(output) if !output.is_empty() => {
-                                clipboard.set_text(output.replace("{icon}", &id))?;
-                                messages.push(Message {
-                                    message: "Custom output copied to clipboard.".to_string(),
-                                    color: Color::Green,
-                                    delete_at: chrono::Utc::now()
-                                        .checked_add_signed(TimeDelta::seconds(2))
-                                        .unwrap(),
-                                });
-                            }
-                            _ => {
+                        let id = results.page[selected_index as usize].clone();
+
+                        match template.as_deref().and_then(|name| config.template(name)) {
+                            Some(output) => match render_template(
+                                output,
+                                &id,
+                                collections_cache,
+                                search_api.as_deref(),
+                            ) {
+                                Ok(output) => {
+                                    clipboard.set_text(output)?;
+                                    messages.push(Message {
+                                        message: format!(
+                                            "Output of template '{}' copied to clipboard.",
+                                            template.as_deref().unwrap_or_default()
+                                        ),
+                                        color: Color::Green,
+                                        delete_at: chrono::Utc::now()
+                                            .checked_add_signed(TimeDelta::seconds(2))
+                                            .unwrap(),
+                                    });
+                                }
+                                Err(e) => {
+                                    messages.push(Message {
+                                        message: e.to_string(),
+                                        color: Color::Red,
+                                        delete_at: chrono::Utc::now()
+                                            .checked_add_signed(TimeDelta::seconds(2))
+                                            .unwrap(),
+                                    });
+                                }
+                            },
+                            None => {
                                 messages.push(Message {
-                                    message: "No custom output defined.".to_string(),
+                                    message: "No output template defined.".to_string(),
                                     color: Color::Yellow,
                                     delete_at: chrono::Utc::now()
                                         .checked_add_signed(TimeDelta::seconds(2))
@@ -398,13 +567,34 @@ pub fn browse(
                             }
                         }
                     }
+                    KeyCode::Char('o') => {
+                        // Cycle through the output templates used by C and after selecting.
+                        // The presets are always there, so the list is never empty.
+                        let names = config.template_names();
+
+                        let next = template
+                            .as_ref()
+                            .and_then(|current| names.iter().position(|n| n == current))
+                            .map_or(0, |i| (i + 1) % names.len());
+                        template = Some(names[next].clone());
+
+                        messages.push(Message {
+                            message: format!("Using output template '{}'", names[next]),
+                            color: Color::Blue,
+                            delete_at: chrono::Utc::now()
+                                .checked_add_signed(TimeDelta::seconds(2))
+                                .unwrap(),
+                        });
+                    }
                     KeyCode::Char('g') => {
-                        let current = query_results[selected_index as usize].clone();
+                        let current = results.page[selected_index as usize].clone();
 
                         let (collection_id, _) = current.split_once(":").unwrap();
                         search_string = format!("{}:", collection_id);
                         let (p, _) = parse_search_string(search_string.as_str())?;
-                        query_results = query(&None, &p)?;
+                        results.all = search(&None, &p, &theme, &search_api, config)?;
+                        results.page_offset = 0;
+                        highlighter = Highlighter::new(&None, config)?;
 
                         messages.push(Message {
                             message: format!("Showing collection '{}'", collection_id),
@@ -415,7 +605,72 @@ pub fn browse(
                         });
                         render_query(
                             &mut stdout,
-                            &mut query_results,
+                            &mut results,
+                            &highlighter,
+                            &mut selected_index,
+                            &mut previously_selected_index,
+                            collections_cache,
+                            fontdb,
+                        )?;
+                    }
+                    KeyCode::Char('v') if !results.page.is_empty() => {
+                        let current = results.page[selected_index as usize].clone();
+
+                        results.all =
+                            find_similar(&current, SIMILAR_RESULTS, collections_cache, fontdb)?;
+                        // Keep the icon itself first, for comparison.
+                        results.all.insert(0, current.clone());
+                        results.page_offset = 0;
+                        highlighter = Highlighter::new(&None, config)?;
+
+                        messages.push(Message {
+                            message: format!("Showing icons similar to '{}'", current),
+                            color: Color::Blue,
+                            delete_at: chrono::Utc::now()
+                                .checked_add_signed(TimeDelta::seconds(2))
+                                .unwrap(),
+                        });
+                        render_query(
+                            &mut stdout,
+                            &mut results,
+                            &highlighter,
+                            &mut selected_index,
+                            &mut previously_selected_index,
+                            collections_cache,
+                            fontdb,
+                        )?;
+                    }
+                    KeyCode::Char('t') => {
+                        // Cycle through the theme filters, ending with no filter.
+                        theme = match &theme {
+                            None => Some(THEMES[0].to_string()),
+                            Some(current) => THEMES
+                                .iter()
+                                .position(|t| t == current)
+                                .and_then(|i| THEMES.get(i + 1))
+                                .map(|t| t.to_string()),
+                        };
+
+                        let (p, q) = parse_search_string(&search_string)?;
+                        results.all = search(&q, &p, &theme, &search_api, config)?;
+                        results.page_offset = 0;
+                        highlighter = Highlighter::new(&q, config)?;
+
+                        let message = match &theme {
+                            Some(theme) => format!("Showing '{}' icons", theme),
+                            None => "Showing icons in all themes".to_string(),
+                        };
+                        messages.push(Message {
+                            message,
+                            color: Color::Blue,
+                            delete_at: chrono::Utc::now()
+                                .checked_add_signed(TimeDelta::seconds(2))
+                                .unwrap(),
+                        });
+                        render_query(
+                            &mut stdout,
+                            &mut results,
+                            &highlighter,
                             &mut selected_index,
                             &mut previously_selected_index,
                             collections_cache,
@@ -428,7 +683,7 @@ pub fn browse(
                             Direction::Down,
                             &mut selected_index,
                             &mut previously_selected_index,
-                            query_results.len() as u16,
+                            results.page.len() as u16,
                             &cols,
                         );
                     }
@@ -438,7 +693,7 @@ pub fn browse(
                             Direction::Up,
                             &mut selected_index,
                             &mut previously_selected_index,
-                            query_results.len() as u16,
+                            results.page.len() as u16,
                             &cols,
                         );
                     }
@@ -448,7 +703,7 @@ pub fn browse(
                             Direction::Left,
                             &mut selected_index,
                             &mut previously_selected_index,
-                            query_results.len() as u16,
+                            results.page.len() as u16,
                             &cols,
                         );
                     }
@@ -458,7 +713,7 @@ pub fn browse(
                             Direction::Right,
                             &mut selected_index,
                             &mut previously_selected_index,
-                            query_results.len() as u16,
+                            results.page.len() as u16,
                             &cols,
                         );
                     }
@@ -472,32 +727,30 @@ pub fn browse(
         let mut col = 2;
         let mut row = 1;
 
-        let num_results = query_results.iter().len() as u16;
+        let num_results = results.page.iter().len() as u16;
 
         for i in 0..num_results {
-            let i = i as u16;
-
             if let Some(psi) = previously_selected_index {
                 if i == psi {
-                    stdout.queue(MoveTo(col as u16, row))?;
+                    stdout.queue(MoveTo(col, row))?;
                     stdout.queue(Clear(ClearType::UntilNewLine))?;
-                    stdout.queue(MoveTo(col as u16, row + 1))?;
+                    stdout.queue(MoveTo(col, row + 1))?;
                     stdout.queue(Clear(ClearType::UntilNewLine))?;
-                    stdout.queue(MoveTo(col as u16, row + 2))?;
+                    stdout.queue(MoveTo(col, row + 2))?;
                     stdout.queue(Clear(ClearType::UntilNewLine))?;
                     stdout.flush().unwrap();
                 }
             }
 
             if i == selected_index {
-                stdout.queue(MoveTo(col as u16, row))?;
+                stdout.queue(MoveTo(col, row))?;
                 stdout.queue(SetForegroundColor(Color::White))?;
                 stdout.queue(SetBackgroundColor(Color::Blue))?;
-                stdout.queue(Print(format!("      ")))?;
-                stdout.queue(MoveTo(col as u16, row + 1))?;
-                stdout.queue(Print(format!("      ")))?;
-                stdout.queue(MoveTo(col as u16, row + 2))?;
-                stdout.queue(Print(format!("      ")))?;
+                stdout.queue(Print("      ".to_string()))?;
+                stdout.queue(MoveTo(col, row + 1))?;
+                stdout.queue(Print("      ".to_string()))?;
+                stdout.queue(MoveTo(col, row + 2))?;
+                stdout.queue(Print("      ".to_string()))?;
                 stdout.queue(SetForegroundColor(Color::Reset))?;
                 stdout.queue(SetBackgroundColor(Color::Reset))?;
             }
@@ -512,7 +765,8 @@ pub fn browse(
             };
         }
 
-        if args.verbose {
+        // The grid details are shown with --verbose.
+        if log_enabled!(Level::Debug) {
             stdout.queue(MoveTo(1, rows - 5))?;
             stdout.queue(Clear(ClearType::CurrentLine))?;
             stdout.queue(Print(format!("Index: {}", selected_index)))?;
@@ -542,10 +796,31 @@ pub fn browse(
         stdout.queue(MoveTo(1, rows - 1))?;
         stdout.queue(Clear(ClearType::CurrentLine))?;
 
-        if search_mode {
+        if search_mode && history.is_reverse_searching() {
+            stdout.queue(Print(format!(
+                "(reverse-search)`{}': {}",
+                history.reverse_search_pattern(),
+                history.reverse_search_match().unwrap_or_default()
+            )))?;
+        } else if search_mode {
             stdout.queue(Print(format!("Enter search: {}", search_string)))?;
-        } else if !query_results.is_empty() {
-            stdout.queue(Print(query_results[selected_index as usize].clone()))?;
+        } else if !results.page.is_empty() {
+            print_highlighted(
+                &mut stdout,
+                &results.page[selected_index as usize],
+                &highlighter,
+            )?;
+
+            // Show where on the pages of results we are, when there's more than one page.
+            if results.page.len() < results.all.len() {
+                let summary =
+                    page_summary(results.page_offset, results.page.len(), results.all.len());
+                stdout.queue(MoveTo(
+                    cols.saturating_sub(summary.len() as u16 + 1),
+                    rows - 1,
+                ))?;
+                stdout.queue(Print(summary))?;
+            }
         }
 
         stdout.flush().unwrap();
@@ -572,36 +847,19 @@ pub fn browse(
     terminal::disable_raw_mode()?;
 
     if let Some(selected) = selected {
-        println!("{}", selected);
-        println!("");
-        print!("  ");
-        preview(&selected, collections_cache, fontdb)?;
-        println!("");
-
-        match &selected.split_once(":") {
-            Some((collection_id, _icon_id)) => {
-                let collection = get_collection(collection_id)?;
-                print!("License: {}", collection.info.license.title);
-                if let Some(license_url) = collection.info.license.url {
-                    println!(": {}", license_url);
-                } else {
-                    println!("");
-                }
-            }
-            None => {}
-        }
+        print_summary(&selected, collections_cache, search_api.as_deref(), fontdb)?;
 
-        let config = read_config_file()?;
-
-        if let Some(output) = config.custom_output {
-            if !output.is_empty() {
-                println!("");
-                println!("{}", output.replace("{icon}", &selected));
-            }
+        if let Some(output) = template.as_deref().and_then(|name| config.template(name)) {
+            println!();
+            println!(
+                "{}",
+                render_template(output, &selected, collections_cache, search_api.as_deref())?
+            );
         }
 
         if args.output_svg {
-            let (width, height, body) = get_icon_xml(&selected, collections_cache)?;
+            let (width, height, body) =
+                get_icon_xml(&selected, collections_cache, search_api.as_deref())?;
 
             let header = format!(
                 r#"<svg xmlns="http://www.w3.org/2000/svg" width="96" height="96" color="white" viewBox="0 0 {} {}">"#,
@@ -610,11 +868,11 @@ pub fn browse(
             let footer = r#"</svg>"#;
 
             let body = body.replace("stroke=\"#000\"", "stroke=\"#fff\"");
-            println!("");
+            println!();
             println!("{}{}{}", header, body, footer);
         }
 
-        println!("");
+        println!();
     }
 
     stdout.queue(cursor::Show)?;
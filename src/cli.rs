@@ -1,44 +1,640 @@
-use clap::{ArgAction, Parser};
+use std::path::PathBuf;
+
+use clap::{ArgAction, Args, Parser, Subcommand, ValueEnum};
+
+use crate::render::Padding;
+
+/// Shown below the options, as a query such as `icon font` can't be told apart
+/// from the subcommand of the same name.
+const QUERY_HELP: &str =
+    "A query that is also the name of a command, such as `icon font` or `icon css`, runs \
+that command. Use `icon search <QUERY>` to search for it instead.";
 
 #[derive(Parser)]
-#[command(about, long_about = None)]
+#[command(
+    about,
+    long_about = None,
+    args_conflicts_with_subcommands = true,
+    after_help = QUERY_HELP
+)]
 pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    // Searching without a subcommand, e.g. `icon home`, works like the search
+    // subcommand and opens the browser with --browse or the default_browse config.
+    #[command(flatten)]
+    pub search: SearchArgs,
+
+    /// Open a browser to preview and select icons.
+    #[clap(short = 'b', long = "browse", action=ArgAction::SetTrue)]
+    pub browse: bool,
+
+    /// Disables the browser for the current session if default browse config is set.
+    #[clap(long = "no-browse", action=ArgAction::SetTrue)]
+    pub no_browse: bool,
+
+    /// After selecting an icon in the browser, print the SVG source of the icon.
+    #[clap(short = 's', long = "svg", action=ArgAction::SetTrue)]
+    pub output_svg: bool,
+
+    /// The output template to use in browse mode, from the [templates] table of the
+    /// config or a built-in preset such as iconify-react, unocss or tailwind.
+    #[clap(long = "template")]
+    pub template: Option<String>,
+
+    /// Print more verbose debug messages. Goes after the subcommand, if there is one.
+    #[clap(short = 'v', long = "verbose", global = true, action=ArgAction::SetTrue)]
+    pub verbose: bool,
+}
+
+impl Cli {
+    /// The browse options of a search without a subcommand.
+    pub fn browse_args(&self) -> BrowseArgs {
+        BrowseArgs {
+            query: self.search.query.clone(),
+            output_svg: self.output_svg,
+            template: self.template.clone(),
+        }
+    }
+}
+
+// What to search for, shared by the search and browse subcommands.
+#[derive(Args, Clone, Default)]
+pub struct QueryArgs {
     /// Select an icon collection by prefix. Can be used without query.
     #[clap(short = 'p', long = "prefix")]
     pub prefix: Option<String>,
 
-    /// Generate the icon cache.
-    #[clap(long = "generate-icons-cache", action=ArgAction::SetTrue)]
-    pub generate_icons_cache: bool,
+    /// Only show icons in a theme of their collection, e.g. outline, filled, rounded or duotone.
+    #[clap(short = 't', long = "theme")]
+    pub theme: Option<String>,
 
-    /// Fetch the icon collections.
-    #[clap(long = "fetch-collections", action=ArgAction::SetTrue)]
-    pub fetch_collections: bool,
+    /// Search through an Iconify API instead of the local icons cache.
+    #[clap(short = 'r', long = "remote", action=ArgAction::SetTrue)]
+    pub remote: bool,
+
+    /// Disables remote search for the current session if remote search config is set.
+    #[clap(long = "no-remote", action=ArgAction::SetTrue)]
+    pub no_remote: bool,
+
+    /// The Iconify API to use for remote searches, e.g. a self-hosted instance.
+    #[clap(long = "search-api", value_name = "URL")]
+    pub search_api: Option<String>,
+
+    /// The query string to search for. Supports the filters style:stroke, style:fill,
+    /// color:mono, color:multi, animated:true and animated:false.
+    pub query: Option<String>,
+}
+
+#[derive(Args)]
+pub struct SearchArgs {
+    #[command(flatten)]
+    pub query: QueryArgs,
 
     /// Render previews in addition to displaying the icon identifiers.
     #[clap(long = "preview", action=ArgAction::SetTrue)]
     pub preview: bool,
 
-    /// Open a browser to preview and select icons.
-    #[clap(short = 'b', long = "browse", action=ArgAction::SetTrue)]
-    pub browse: bool,
+    /// The maximum number of icons to show.
+    #[clap(long = "limit", value_name = "N")]
+    pub limit: Option<usize>,
 
-    /// Disables the browser for the current session if default browse config is set.
-    #[clap(long = "no-browse", action=ArgAction::SetTrue)]
-    pub no_browse: bool,
+    /// The number of icons to skip before showing any.
+    #[clap(long = "offset", value_name = "N", default_value_t = 0)]
+    pub offset: usize,
 
-    /// Print more verbose debug messages.
-    #[clap(short = 'v', long = "verbose", action=ArgAction::SetTrue)]
-    pub verbose: bool,
+    /// Only print the number of matching icons.
+    #[clap(long = "count", action=ArgAction::SetTrue)]
+    pub count: bool,
 
-    /// After selecting an icon in the browser, print the SVG source of the icon.
+    /// The format to print the found icons in.
+    #[clap(short = 'f', long = "format", value_enum, default_value_t = OutputFormat::Plain)]
+    pub format: OutputFormat,
+
+    /// Include the SVG body of the icons in json, ndjson and tsv output.
+    #[clap(long = "with-body", action=ArgAction::SetTrue)]
+    pub with_body: bool,
+
+    /// Find the icons that look the most like an icon identifier or an SVG/PNG file.
+    #[clap(long = "similar", value_name = "ICON_OR_FILE")]
+    pub similar: Option<String>,
+
+    /// Print the search history, oldest search first.
+    #[clap(long = "history", action=ArgAction::SetTrue)]
+    pub history: bool,
+}
+
+#[derive(Args)]
+pub struct BrowseArgs {
+    #[command(flatten)]
+    pub query: QueryArgs,
+
+    /// After selecting an icon, print the SVG source of the icon.
     #[clap(short = 's', long = "svg", action=ArgAction::SetTrue)]
     pub output_svg: bool,
 
-    /// Read and print the config.
-    #[clap(long = "reset-config", action=ArgAction::SetTrue)]
-    pub reset_config: bool,
+    /// The output template to use, from the [templates] table of the config or a
+    /// built-in preset such as iconify-react, unocss or tailwind.
+    #[clap(long = "template")]
+    pub template: Option<String>,
+}
 
-    /// The query string to search for.
-    pub query: Option<String>,
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    /// One icon identifier per line.
+    Plain,
+    /// A JSON array with the details of every icon.
+    Json,
+    /// One JSON object with the details of an icon per line.
+    Ndjson,
+    /// Tab separated values with a header row.
+    Tsv,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Search for icons and print their identifiers.
+    Search(SearchArgs),
+
+    /// Open a browser to preview and select icons.
+    Browse(BrowseArgs),
+
+    /// Print the details of an icon: its collection, license, aliases, themes and SVG.
+    Show(ShowArgs),
+
+    /// Write standalone SVG files for one or more icons.
+    Export(ExportArgs),
+
+    /// Write PNG or WebP images of one or more icons, at several sizes and densities.
+    Raster(RasterArgs),
+
+    /// Write a favicon.ico, PWA manifest icons, an SVG favicon and the HTML to use them.
+    Favicon(FaviconArgs),
+
+    /// Write the launcher icons of an Android app and the app icon set of an iOS app.
+    AppIcons(AppIconsArgs),
+
+    /// Write an SVG sprite of <symbol> elements and a manifest of their ids.
+    Sprite(SpriteArgs),
+
+    /// Generate a component for each icon and an index file that exports them.
+    Components(ComponentsArgs),
+
+    /// Build an icon font from icons, with a stylesheet and a map of the codepoints.
+    Font(FontArgs),
+
+    /// Generate CSS classes that draw icons from inline SVG data URIs.
+    Css(CssArgs),
+
+    /// List the icon collections, with their size, license and category.
+    Collections(CollectionsArgs),
+
+    /// Fetch the icon collections and generate the icon cache.
+    #[command(subcommand)]
+    Cache(CacheCommand),
+
+    /// Manage the config file.
+    #[command(subcommand)]
+    Config(ConfigCommand),
+}
+
+#[derive(Args)]
+pub struct ShowArgs {
+    /// The icon to show, e.g. mdi:home.
+    pub icon: String,
+
+    /// Print the details as JSON.
+    #[clap(long = "json", action=ArgAction::SetTrue)]
+    pub json: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum CollectionSort {
+    Prefix,
+    Name,
+    /// The number of icons, largest first.
+    Icons,
+    Category,
+    Height,
+}
+
+#[derive(Args)]
+pub struct CollectionsArgs {
+    /// Only list collections with this text in their prefix, name or author.
+    pub filter: Option<String>,
+
+    /// Only list collections in a category, e.g. general, emoji or logos.
+    #[clap(long = "category")]
+    pub category: Option<String>,
+
+    /// Only list collections under a license, by its SPDX identifier, e.g. MIT.
+    #[clap(long = "license")]
+    pub license: Option<String>,
+
+    /// Only list collections with icons of this height.
+    #[clap(long = "height")]
+    pub height: Option<usize>,
+
+    /// The order to list the collections in.
+    #[clap(long = "sort", value_enum, default_value_t = CollectionSort::Prefix)]
+    pub sort: CollectionSort,
+
+    /// Reverse the order.
+    #[clap(long = "reverse", action=ArgAction::SetTrue)]
+    pub reverse: bool,
+
+    /// The maximum number of collections to list.
+    #[clap(long = "limit", value_name = "N")]
+    pub limit: Option<usize>,
+
+    /// Render the sample icons of each collection. Needs --limit when more than 20
+    /// collections match, as every previewed collection is downloaded.
+    #[clap(long = "preview", action=ArgAction::SetTrue)]
+    pub preview: bool,
+
+    /// The format to print the collections in.
+    #[clap(short = 'f', long = "format", value_enum, default_value_t = OutputFormat::Plain)]
+    pub format: OutputFormat,
+}
+
+#[derive(Subcommand)]
+pub enum CacheCommand {
+    /// Fetch the icon collections.
+    Fetch,
+
+    /// Generate the icon cache from the fetched collections.
+    Generate,
+
+    /// Print the directory of the cache.
+    Path,
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// Print the config file.
+    Show,
+
+    /// Replace the config file with the default config.
+    Reset,
+
+    /// Print the path of the config file.
+    Path,
+}
+
+#[derive(Args)]
+pub struct ExportArgs {
+    /// The icons to export, e.g. mdi:home.
+    #[clap(required = true)]
+    pub icons: Vec<String>,
+
+    /// The height of the SVG, e.g. 24 or 1.5em. The width follows from the aspect ratio of the icon.
+    #[clap(long = "size")]
+    pub size: Option<String>,
+
+    /// The colour of monochrome icons. Defaults to currentColor, which inherits the text colour.
+    #[clap(long = "color", default_value = "currentColor")]
+    pub color: String,
+
+    /// Override the width of the strokes in stroke based icons.
+    #[clap(long = "stroke-width")]
+    pub stroke_width: Option<String>,
+
+    /// The directory to write the files to.
+    #[clap(short = 'o', long = "out-dir", default_value = ".")]
+    pub out_dir: PathBuf,
+
+    /// The filename template, with {prefix} and {name} placeholders. May contain directories.
+    #[clap(long = "filename", default_value = "{prefix}-{name}.svg")]
+    pub filename: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum RasterFormat {
+    Png,
+    Webp,
+}
+
+#[derive(Args)]
+pub struct RasterArgs {
+    /// The icons to export, e.g. mdi:home.
+    #[clap(required = true)]
+    pub icons: Vec<String>,
+
+    /// The sizes to export, in pixels at 1x. Can be given several times or comma separated.
+    #[clap(
+        short = 's',
+        long = "size",
+        value_delimiter = ',',
+        default_value = "24"
+    )]
+    pub sizes: Vec<u32>,
+
+    /// The densities to export, e.g. 1,2,3 for @1x, @2x and @3x images.
+    #[clap(long = "scale", value_delimiter = ',', default_value = "1")]
+    pub scales: Vec<u32>,
+
+    /// The image format.
+    #[clap(long = "format", value_enum, default_value_t = RasterFormat::Png)]
+    pub format: RasterFormat,
+
+    /// The colour of monochrome icons.
+    #[clap(long = "color", default_value = "#000")]
+    pub color: String,
+
+    /// The space around the icon, in pixels at 1x (e.g. 2) or as a percentage of the size (e.g. 10%).
+    #[clap(long = "padding", default_value = "0")]
+    pub padding: Padding,
+
+    /// Fill the image with a background colour instead of leaving it transparent.
+    #[clap(long = "background")]
+    pub background: Option<String>,
+
+    /// The directory to write the files to.
+    #[clap(short = 'o', long = "out-dir", default_value = ".")]
+    pub out_dir: PathBuf,
+
+    /// The filename template, with {prefix}, {name}, {size}, {scale} and {ext} placeholders.
+    /// {scale} is empty at 1x and e.g. @2x otherwise.
+    #[clap(
+        long = "filename",
+        default_value = "{prefix}-{name}-{size}{scale}.{ext}"
+    )]
+    pub filename: String,
+}
+
+#[derive(Args)]
+pub struct FaviconArgs {
+    /// The icon to use, e.g. mdi:home.
+    pub icon: String,
+
+    /// The colour of the icon, if it is monochrome.
+    #[clap(long = "color", default_value = "#000")]
+    pub color: String,
+
+    /// The background colour. The icons are transparent if not set, except for the
+    /// Apple touch icon which is always opaque.
+    #[clap(long = "background")]
+    pub background: Option<String>,
+
+    /// The space around the icon, as a percentage of the size (e.g. 10%) or in pixels.
+    #[clap(long = "padding", default_value = "0")]
+    pub padding: Padding,
+
+    /// The radius of the corners of the background, as a percentage of the size.
+    #[clap(long = "corner-radius", default_value = "0", value_parser = parse_percentage)]
+    pub corner_radius: f32,
+
+    /// The name of the app in the manifest. Defaults to the name of the icon.
+    #[clap(long = "name")]
+    pub name: Option<String>,
+
+    /// The theme colour in the manifest and HTML. Defaults to the background colour.
+    #[clap(long = "theme-color")]
+    pub theme_color: Option<String>,
+
+    /// The path the files are served from, used in the HTML and manifest.
+    #[clap(long = "base-path", default_value = "/")]
+    pub base_path: String,
+
+    /// The directory to write the files to.
+    #[clap(short = 'o', long = "out-dir", default_value = ".")]
+    pub out_dir: PathBuf,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum Platform {
+    Android,
+    Ios,
+}
+
+#[derive(Args)]
+pub struct AppIconsArgs {
+    /// The icon to use, e.g. mdi:home.
+    pub icon: String,
+
+    /// The platforms to write icons for. Can be given several times or comma separated.
+    #[clap(long = "platform", value_enum, value_delimiter = ',', default_values_t = [Platform::Android, Platform::Ios])]
+    pub platforms: Vec<Platform>,
+
+    /// The colour of the icon, if it is monochrome.
+    #[clap(long = "color", default_value = "#000")]
+    pub color: String,
+
+    /// The background colour. App icons are always opaque.
+    #[clap(long = "background", default_value = "#fff")]
+    pub background: String,
+
+    /// The space around the icon, as a percentage of the size (e.g. 10%) or in pixels at 1x.
+    /// On Android adaptive icons it is added inside the safe zone.
+    #[clap(long = "padding", default_value = "0")]
+    pub padding: Padding,
+
+    /// The radius of the corners of legacy Android icons, as a percentage of the size.
+    #[clap(long = "corner-radius", default_value = "0", value_parser = parse_percentage)]
+    pub corner_radius: f32,
+
+    /// The directory to write the android and ios directories to.
+    #[clap(short = 'o', long = "out-dir", default_value = ".")]
+    pub out_dir: PathBuf,
+}
+
+#[derive(Args)]
+pub struct SpriteArgs {
+    /// The icons to include, e.g. mdi:home. Read from stdin when none are given and it is piped.
+    pub icons: Vec<String>,
+
+    /// A file with icon identifiers, separated by newlines, spaces or commas. Use - for stdin.
+    #[clap(short = 'i', long = "input")]
+    pub input: Option<String>,
+
+    /// The sprite file to write.
+    #[clap(short = 'o', long = "out", default_value = "sprite.svg")]
+    pub out: PathBuf,
+
+    /// The manifest file to write. Defaults to the sprite file with a .json extension.
+    #[clap(long = "manifest")]
+    pub manifest: Option<PathBuf>,
+
+    /// The template of the symbol ids, with {prefix} and {name} placeholders.
+    #[clap(long = "symbol-id", default_value = "{prefix}-{name}")]
+    pub symbol_id: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum Framework {
+    React,
+    Vue,
+    Svelte,
+    Solid,
+    WebComponent,
+    Leptos,
+    Yew,
+    Dioxus,
+    Egui,
+}
+
+impl Framework {
+    /// Rust frameworks get a single module instead of a file per component.
+    pub fn is_rust(self) -> bool {
+        matches!(
+            self,
+            Framework::Leptos | Framework::Yew | Framework::Dioxus | Framework::Egui
+        )
+    }
+}
+
+#[derive(Args)]
+pub struct ComponentsArgs {
+    /// The icons to generate components for, e.g. mdi:home. Read from stdin when none are given and it is piped.
+    pub icons: Vec<String>,
+
+    /// A file with icon identifiers, separated by newlines, spaces or commas. Use - for stdin.
+    #[clap(short = 'i', long = "input")]
+    pub input: Option<String>,
+
+    /// The framework to generate components for.
+    #[clap(short = 'f', long = "framework", value_enum)]
+    pub framework: Framework,
+
+    /// The directory to write the components to.
+    #[clap(short = 'o', long = "out-dir", default_value = ".")]
+    pub out_dir: PathBuf,
+
+    /// The name of the module generated for Rust frameworks.
+    #[clap(long = "module", default_value = "icons")]
+    pub module: String,
+}
+
+#[derive(Args)]
+pub struct FontArgs {
+    /// The icons to include, e.g. mdi:home. Read from stdin when none are given and it is piped.
+    pub icons: Vec<String>,
+
+    /// A file with icon identifiers, separated by newlines, spaces or commas. Use - for stdin.
+    #[clap(short = 'i', long = "input")]
+    pub input: Option<String>,
+
+    /// The font family name, also used for the file names and the base CSS class.
+    #[clap(short = 'n', long = "name", default_value = "icons")]
+    pub name: String,
+
+    /// The first codepoint to assign, in hexadecimal.
+    #[clap(long = "start", default_value = "e000")]
+    pub start: String,
+
+    /// Use the codepoints of the icon fonts of the collections where they exist.
+    #[clap(long = "chars", action=ArgAction::SetTrue)]
+    pub chars: bool,
+
+    /// The template of the CSS class of each icon, with {prefix} and {name} placeholders.
+    #[clap(long = "class", default_value = "icon-{prefix}-{name}")]
+    pub class: String,
+
+    /// The directory to write the font files to.
+    #[clap(short = 'o', long = "out-dir", default_value = ".")]
+    pub out_dir: PathBuf,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum CssNaming {
+    /// Classes like .i-mdi-home
+    Dash,
+    /// Classes like .icon-[mdi--home], as used by Tailwind CSS
+    Bracket,
+}
+
+#[derive(Args)]
+pub struct CssArgs {
+    /// The icons to include, e.g. mdi:home. Read from stdin when none are given and it is piped.
+    pub icons: Vec<String>,
+
+    /// A file with icon identifiers, separated by newlines, spaces or commas. Use - for stdin.
+    #[clap(short = 'i', long = "input")]
+    pub input: Option<String>,
+
+    /// How the classes are named.
+    #[clap(long = "naming", value_enum, default_value_t = CssNaming::Dash)]
+    pub naming: CssNaming,
+
+    /// The height of the icons. The width follows from the aspect ratio of each icon.
+    #[clap(long = "size", default_value = "1em")]
+    pub size: String,
+
+    /// The file to write the stylesheet to, instead of printing it.
+    #[clap(short = 'o', long = "out")]
+    pub out: Option<PathBuf>,
+}
+
+/// Parses a percentage such as `20` or `20%`.
+fn parse_percentage(s: &str) -> Result<f32, String> {
+    s.trim_end_matches('%')
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid percentage '{}'.", s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Cli, clap::Error> {
+        Cli::try_parse_from([&["icon"], args].concat())
+    }
+
+    #[test]
+    fn cli_is_valid() {
+        use clap::CommandFactory;
+
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn bare_query_searches() {
+        let cli = parse(&["home"]).unwrap();
+
+        assert!(cli.command.is_none());
+        assert_eq!(cli.search.query.query.as_deref(), Some("home"));
+
+        let cli = parse(&["-p", "mdi", "--limit", "5", "home"]).unwrap();
+
+        assert!(cli.command.is_none());
+        assert_eq!(cli.search.query.prefix.as_deref(), Some("mdi"));
+        assert_eq!(cli.search.limit, Some(5));
+    }
+
+    #[test]
+    fn subcommands_take_their_own_options() {
+        let cli = parse(&["search", "-p", "mdi", "-v", "home"]).unwrap();
+
+        let Some(Command::Search(args)) = cli.command else {
+            panic!("expected the search subcommand");
+        };
+        assert_eq!(args.query.prefix.as_deref(), Some("mdi"));
+        assert_eq!(args.query.query.as_deref(), Some("home"));
+        assert!(cli.verbose);
+    }
+
+    #[test]
+    fn query_options_conflict_with_subcommands() {
+        assert!(parse(&["home", "export"]).is_err());
+        assert!(parse(&["-b", "show", "mdi:home"]).is_err());
+    }
+
+    #[test]
+    fn subcommand_names_are_not_queries() {
+        use clap::CommandFactory;
+
+        let cli = parse(&["collections"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Collections(_))));
+
+        let cli = parse(&["search", "font"]).unwrap();
+        let Some(Command::Search(args)) = cli.command else {
+            panic!("expected the search subcommand");
+        };
+        assert_eq!(args.query.query.as_deref(), Some("font"));
+
+        let help = Cli::command().render_help().to_string();
+        assert!(help.contains("Use `icon search <QUERY>` to search for it instead."));
+    }
 }
@@ -0,0 +1,211 @@
+use std::{collections::HashMap, error::Error};
+
+use log::info;
+use resvg::usvg::fontdb::Database;
+use serde::Serialize;
+
+use crate::{
+    cli::{CollectionSort, CollectionsArgs, OutputFormat},
+    enums::{Collection, IconCollection},
+    files::{get_cached_collections, get_icon_xml, render_svg},
+    search::page,
+};
+
+/// The size of a sample icon in the preview, in pixels, and the space between them.
+const SAMPLE_SIZE: u32 = 96;
+const SAMPLE_GAP: u32 = 16;
+
+/// The most collections previewed without --limit, as each of them is downloaded.
+const MAX_UNLIMITED_PREVIEWS: usize = 20;
+
+/// The details of a collection, as printed by the json, ndjson and tsv formats.
+#[derive(Debug, Serialize)]
+pub struct CollectionRecord {
+    pub prefix: String,
+    #[serde(flatten)]
+    pub info: Collection,
+}
+
+impl CollectionRecord {
+    const TSV_HEADER: [&'static str; 8] = [
+        "prefix", "name", "total", "license", "author", "category", "height", "palette",
+    ];
+
+    fn fields(&self) -> [String; 8] {
+        [
+            self.prefix.clone(),
+            self.info.name.clone(),
+            self.info.total.to_string(),
+            self.info.license.spdx.clone(),
+            self.info.author.name.clone(),
+            self.info.category.clone().unwrap_or_default(),
+            self.info.height.map(|h| h.to_string()).unwrap_or_default(),
+            self.info.palette.to_string(),
+        ]
+    }
+}
+
+fn matches(record: &CollectionRecord, args: &CollectionsArgs) -> bool {
+    let contains = |value: &str, text: &str| value.to_lowercase().contains(&text.to_lowercase());
+    let info = &record.info;
+
+    args.filter.as_ref().is_none_or(|filter| {
+        contains(&record.prefix, filter)
+            || contains(&info.name, filter)
+            || contains(&info.author.name, filter)
+    }) && args.category.as_ref().is_none_or(|category| {
+        info.category
+            .as_ref()
+            .is_some_and(|c| contains(c, category))
+    }) && args
+        .license
+        .as_ref()
+        .is_none_or(|license| info.license.spdx.eq_ignore_ascii_case(license))
+        && args.height.is_none_or(|height| info.height == Some(height))
+}
+
+/// Renders the sample icons of a collection next to each other in the terminal.
+/// Samples that can't be read are left out.
+fn preview_samples(
+    record: &CollectionRecord,
+    collections_cache: &mut HashMap<String, IconCollection>,
+    fontdb: &Database,
+) -> Result<(), Box<dyn Error>> {
+    let mut body = String::new();
+    let mut x = 0;
+
+    for sample in &record.info.samples {
+        let icon = format!("{}:{}", record.prefix, sample);
+        let (width, height, sample_body) = match get_icon_xml(&icon, collections_cache, None) {
+            Ok(data) => data,
+            Err(e) => {
+                info!("Skipping the {} sample: {}", icon, e);
+                continue;
+            }
+        };
+
+        body.push_str(&format!(
+            r#"<svg x="{}" width="{1}" height="{1}" viewBox="0 0 {2} {3}">{4}</svg>"#,
+            x,
+            SAMPLE_SIZE,
+            width,
+            height,
+            sample_body.replace("stroke=\"#000\"", "stroke=\"#fff\"")
+        ));
+        x += SAMPLE_SIZE + SAMPLE_GAP;
+    }
+
+    if body.is_empty() {
+        return Ok(());
+    }
+
+    let width = x - SAMPLE_GAP;
+    let svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" color="white" viewBox="0 0 {0} {1}">{2}</svg>"#,
+        width, SAMPLE_SIZE, body
+    );
+
+    let out_file = "/tmp/icon-rs-samples.png";
+    render_svg(svg.as_bytes(), fontdb)?.save_png(out_file)?;
+
+    let config = viuer::Config {
+        absolute_offset: false,
+        x: 2,
+        width: Some(width.div_ceil(16)),
+        restore_cursor: false,
+        ..Default::default()
+    };
+    viuer::print_from_file(out_file, &config)?;
+
+    Ok(())
+}
+
+/// Lists the collections that match the filters, in the chosen order.
+pub fn collections(
+    args: &CollectionsArgs,
+    collections_cache: &mut HashMap<String, IconCollection>,
+    fontdb: &Database,
+) -> Result<(), Box<dyn Error>> {
+    let mut records: Vec<CollectionRecord> = get_cached_collections()?
+        .into_iter()
+        .map(|(prefix, info)| CollectionRecord { prefix, info })
+        .filter(|record| matches(record, args))
+        .collect();
+
+    records.sort_by(|a, b| match args.sort {
+        CollectionSort::Prefix => a.prefix.cmp(&b.prefix),
+        CollectionSort::Name => a.info.name.to_lowercase().cmp(&b.info.name.to_lowercase()),
+        CollectionSort::Icons => b.info.total.cmp(&a.info.total),
+        CollectionSort::Category => a.info.category.cmp(&b.info.category),
+        CollectionSort::Height => a.info.height.cmp(&b.info.height),
+    });
+    if args.reverse {
+        records.reverse();
+    }
+
+    if args.preview && args.limit.is_none() && records.len() > MAX_UNLIMITED_PREVIEWS {
+        return Err(format!(
+            "--preview downloads every listed collection, pass --limit or narrow the filters to preview fewer than all {} collections.",
+            records.len()
+        )
+        .into());
+    }
+
+    let shown = page(&records, 0, args.limit);
+
+    match args.format {
+        OutputFormat::Plain => {
+            let rows: Vec<[String; 8]> = shown.iter().map(CollectionRecord::fields).collect();
+
+            // Pad the prefix, name and total columns, so the others line up.
+            let widths: Vec<usize> = (0..3)
+                .map(|i| rows.iter().map(|row| row[i].len()).max().unwrap_or(0))
+                .collect();
+
+            for (record, row) in shown.iter().zip(&rows) {
+                let mut columns = vec![
+                    format!("{:<1$}", row[0], widths[0]),
+                    format!("{:<1$}", row[1], widths[1]),
+                    format!("{:>1$} icons", row[2], widths[2]),
+                    row[3].clone(),
+                ];
+                if !row[5].is_empty() {
+                    columns.push(row[5].clone());
+                }
+                if !row[6].is_empty() {
+                    columns.push(format!("{}px", row[6]));
+                }
+                println!("{}", columns.join("  "));
+
+                if args.preview {
+                    preview_samples(record, collections_cache, fontdb)?;
+                    println!();
+                }
+            }
+        }
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(shown)?),
+        OutputFormat::Ndjson => {
+            for record in shown {
+                println!("{}", serde_json::to_string(record)?);
+            }
+        }
+        OutputFormat::Tsv => {
+            println!("{}", CollectionRecord::TSV_HEADER.join("\t"));
+
+            for record in shown {
+                // Tabs and line breaks would break up the columns and rows.
+                let fields = record
+                    .fields()
+                    .map(|field| field.replace(['\t', '\n', '\r'], " "));
+                println!("{}", fields.join("\t"));
+            }
+        }
+    }
+
+    // The footer goes to stderr, so the output can still be piped.
+    if shown.len() < records.len() {
+        eprintln!("Showing {} of {} collections.", shown.len(), records.len());
+    }
+
+    Ok(())
+}
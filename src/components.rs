@@ -0,0 +1,425 @@
+use std::{collections::HashMap, error::Error};
+
+use crate::{
+    cli::{ComponentsArgs, Framework},
+    enums::IconCollection,
+    export::read_icon_ids,
+    files::{get_icon_xml, write_file},
+    rust_components::rust_module,
+};
+
+const REACT_TEMPLATE: &str = r#"import type { SVGProps } from "react";
+
+const body = {body};
+
+export interface {component}Props extends SVGProps<SVGSVGElement> {
+  size?: string | number;
+  color?: string;
+  title?: string;
+}
+
+export default function {component}({
+  size = "1em",
+  color = "currentColor",
+  title,
+  ...props
+}: {component}Props) {
+  return (
+    <svg
+      xmlns="http://www.w3.org/2000/svg"
+      viewBox="{view_box}"
+      width={size}
+      height={size}
+      color={color}
+      role={title ? "img" : undefined}
+      aria-hidden={title ? undefined : true}
+      {...props}
+    >
+      {title && <title>{title}</title>}
+      <g dangerouslySetInnerHTML={{ __html: body }} />
+    </svg>
+  );
+}
+"#;
+
+const SOLID_TEMPLATE: &str = r#"import { splitProps, type JSX } from "solid-js";
+
+const body = {body};
+
+export interface {component}Props extends JSX.SvgSVGAttributes<SVGSVGElement> {
+  size?: string | number;
+  color?: string;
+  title?: string;
+}
+
+export default function {component}(props: {component}Props) {
+  const [local, others] = splitProps(props, ["size", "color", "title"]);
+
+  return (
+    <svg
+      xmlns="http://www.w3.org/2000/svg"
+      viewBox="{view_box}"
+      width={local.size ?? "1em"}
+      height={local.size ?? "1em"}
+      color={local.color ?? "currentColor"}
+      role={local.title ? "img" : undefined}
+      aria-hidden={local.title ? undefined : true}
+      {...others}
+    >
+      {local.title && <title>{local.title}</title>}
+      <g innerHTML={body} />
+    </svg>
+  );
+}
+"#;
+
+const VUE_TEMPLATE: &str = r#"<script setup lang="ts">
+withDefaults(
+  defineProps<{ size?: string | number; color?: string; title?: string }>(),
+  { size: "1em", color: "currentColor" },
+);
+</script>
+
+<template>
+  <svg
+    xmlns="http://www.w3.org/2000/svg"
+    viewBox="{view_box}"
+    :width="size"
+    :height="size"
+    :color="color"
+    :role="title ? 'img' : undefined"
+    :aria-hidden="title ? undefined : 'true'"
+  >
+    <title v-if="title">{{ title }}</title>
+    <g v-pre>{body}</g>
+  </svg>
+</template>
+"#;
+
+const SVELTE_TEMPLATE: &str = r#"<script lang="ts">
+  export let size: string | number = "1em";
+  export let color = "currentColor";
+  export let title: string | undefined = undefined;
+</script>
+
+<svg
+  xmlns="http://www.w3.org/2000/svg"
+  viewBox="{view_box}"
+  width={size}
+  height={size}
+  {color}
+  role={title ? "img" : undefined}
+  aria-hidden={title ? undefined : "true"}
+  {...$$restProps}
+>
+  {#if title}<title>{title}</title>{/if}
+  {body}
+</svg>
+"#;
+
+const WEB_COMPONENT_TEMPLATE: &str = r#"const body = {body};
+
+export class {component} extends HTMLElement {
+  static observedAttributes = ["size", "color", "title"];
+
+  connectedCallback() {
+    this.render();
+  }
+
+  attributeChangedCallback() {
+    this.render();
+  }
+
+  render() {
+    const ns = "http://www.w3.org/2000/svg";
+    const size = this.getAttribute("size") ?? "1em";
+    const title = this.getAttribute("title");
+
+    const svg = document.createElementNS(ns, "svg");
+    svg.setAttribute("viewBox", "{view_box}");
+    svg.setAttribute("width", size);
+    svg.setAttribute("height", size);
+    svg.setAttribute("color", this.getAttribute("color") ?? "currentColor");
+    svg.innerHTML = body;
+
+    if (title) {
+      const element = document.createElementNS(ns, "title");
+      element.textContent = title;
+      svg.prepend(element);
+      svg.setAttribute("role", "img");
+    } else {
+      svg.setAttribute("aria-hidden", "true");
+    }
+
+    this.replaceChildren(svg);
+  }
+}
+
+if (!customElements.get("{tag}")) {
+  customElements.define("{tag}", {component});
+}
+"#;
+
+/// Turns an icon identifier into a component name, e.g. `mdi:arrow-left` into `MdiArrowLeft`.
+pub fn component_name(icon: &str) -> String {
+    icon.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// Errors when two of the icons would get the same name, as the output of one
+/// would overwrite or clash with the other.
+pub fn check_unique_names(
+    icons: &[String],
+    name: impl Fn(&str) -> String,
+    what: &str,
+) -> Result<(), Box<dyn Error>> {
+    let mut seen = HashMap::<String, &str>::new();
+
+    for icon in icons {
+        let name = name(icon);
+        if let Some(other) = seen.get(&name) {
+            return Err(format!(
+                "{} and {} would both get the {} '{}'.",
+                other, icon, what, name
+            )
+            .into());
+        }
+        seen.insert(name, icon);
+    }
+
+    Ok(())
+}
+
+/// Turns an icon identifier into a custom element name, e.g. `mdi:home` into `mdi-home`.
+fn tag_name(icon: &str) -> String {
+    icon.to_ascii_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// The file a component is written to and the module its export is imported from.
+fn component_file(framework: Framework, icon: &str) -> (String, String) {
+    let component = component_name(icon);
+
+    match framework {
+        Framework::React | Framework::Solid => (format!("{}.tsx", component), component),
+        Framework::Vue => (format!("{}.vue", component), format!("{}.vue", component)),
+        Framework::Svelte => (
+            format!("{}.svelte", component),
+            format!("{}.svelte", component),
+        ),
+        Framework::WebComponent => {
+            let tag = tag_name(icon);
+            (format!("{}.js", tag), format!("{}.js", tag))
+        }
+        Framework::Leptos | Framework::Yew | Framework::Dioxus | Framework::Egui => {
+            unreachable!("Rust frameworks get a single module")
+        }
+    }
+}
+
+/// Generates the source of the component for an icon.
+fn component_source(
+    framework: Framework,
+    icon: &str,
+    width: usize,
+    height: usize,
+    body: &str,
+) -> String {
+    let (template, body) = match framework {
+        Framework::React => (
+            REACT_TEMPLATE,
+            serde_json::to_string(body).unwrap_or_default(),
+        ),
+        Framework::Solid => (
+            SOLID_TEMPLATE,
+            serde_json::to_string(body).unwrap_or_default(),
+        ),
+        Framework::Vue => (VUE_TEMPLATE, body.to_string()),
+        // Braces would be read as Svelte expressions.
+        Framework::Svelte => (
+            SVELTE_TEMPLATE,
+            body.replace('{', "&#123;").replace('}', "&#125;"),
+        ),
+        Framework::WebComponent => (
+            WEB_COMPONENT_TEMPLATE,
+            serde_json::to_string(body).unwrap_or_default(),
+        ),
+        Framework::Leptos | Framework::Yew | Framework::Dioxus | Framework::Egui => {
+            unreachable!("Rust frameworks get a single module")
+        }
+    };
+
+    template
+        .replace("{component}", &component_name(icon))
+        .replace("{tag}", &tag_name(icon))
+        .replace("{view_box}", &format!("0 0 {} {}", width, height))
+        .replace("{body}", &body)
+}
+
+/// Generates a component file for each of the icons and an index file that
+/// re-exports them all, so unused icons can be left out by bundlers.
+pub fn components(
+    args: &ComponentsArgs,
+    collections_cache: &mut HashMap<String, IconCollection>,
+) -> Result<(), Box<dyn Error>> {
+    let icons = read_icon_ids(&args.icons, args.input.as_deref())?;
+
+    if args.framework.is_rust() {
+        return rust_module(args, &icons, collections_cache);
+    }
+
+    check_unique_names(&icons, component_name, "component name")?;
+    check_unique_names(
+        &icons,
+        |icon| component_file(args.framework, icon).0,
+        "file name",
+    )?;
+
+    let mut exports = Vec::<String>::new();
+    for icon in &icons {
+        let (width, height, body) = get_icon_xml(icon, collections_cache, None)
+            .map_err(|e| format!("Could not read {}: {}", icon, e))?;
+
+        let (filename, module) = component_file(args.framework, icon);
+        let path = args.out_dir.join(&filename);
+        write_file(
+            &path,
+            component_source(args.framework, icon, width, height, &body).as_bytes(),
+        )?;
+        println!("{}", path.display());
+
+        exports.push(match args.framework {
+            Framework::WebComponent => format!(
+                "export {{ {} }} from \"./{}\";",
+                component_name(icon),
+                module
+            ),
+            Framework::React | Framework::Vue | Framework::Svelte | Framework::Solid => format!(
+                "export {{ default as {} }} from \"./{}\";",
+                component_name(icon),
+                module
+            ),
+            Framework::Leptos | Framework::Yew | Framework::Dioxus | Framework::Egui => {
+                unreachable!("Rust frameworks get a single module")
+            }
+        });
+    }
+
+    let index = match args.framework {
+        Framework::WebComponent => "index.js",
+        Framework::React | Framework::Vue | Framework::Svelte | Framework::Solid => "index.ts",
+        Framework::Leptos | Framework::Yew | Framework::Dioxus | Framework::Egui => {
+            unreachable!("Rust frameworks get a single module")
+        }
+    };
+    let path = args.out_dir.join(index);
+    write_file(&path, (exports.join("\n") + "\n").as_bytes())?;
+    println!("{}", path.display());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &str = r#"<path d="M0 0h24"/><style>{fill:red}</style>"#;
+
+    fn icons(icons: &[&str]) -> Vec<String> {
+        icons.iter().map(|icon| icon.to_string()).collect()
+    }
+
+    #[test]
+    fn names_are_cased_per_kind() {
+        assert_eq!(component_name("mdi:arrow-left"), "MdiArrowLeft");
+        assert_eq!(component_name("fa6-solid:0"), "Fa6Solid0");
+        assert_eq!(component_name("mdi:arrow--left_2"), "MdiArrowLeft2");
+        assert_eq!(tag_name("Mdi:Arrow_Left"), "mdi-arrow-left");
+    }
+
+    #[test]
+    fn clashing_names_are_rejected() {
+        assert!(check_unique_names(
+            &icons(&["mdi:home", "mdi:arrow-left"]),
+            component_name,
+            "name"
+        )
+        .is_ok());
+
+        let error = check_unique_names(
+            &icons(&["mdi:arrow-left", "mdi:home", "mdi:arrow_left"]),
+            component_name,
+            "component name",
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "mdi:arrow-left and mdi:arrow_left would both get the component name 'MdiArrowLeft'."
+        );
+    }
+
+    #[test]
+    fn files_are_named_per_framework() {
+        let file = |framework| component_file(framework, "mdi:arrow-left");
+
+        assert_eq!(
+            file(Framework::React),
+            ("MdiArrowLeft.tsx".to_string(), "MdiArrowLeft".to_string())
+        );
+        assert_eq!(
+            file(Framework::Vue),
+            (
+                "MdiArrowLeft.vue".to_string(),
+                "MdiArrowLeft.vue".to_string()
+            )
+        );
+        assert_eq!(
+            file(Framework::Svelte),
+            (
+                "MdiArrowLeft.svelte".to_string(),
+                "MdiArrowLeft.svelte".to_string()
+            )
+        );
+        assert_eq!(
+            file(Framework::WebComponent),
+            (
+                "mdi-arrow-left.js".to_string(),
+                "mdi-arrow-left.js".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn bodies_are_embedded_per_framework() {
+        let source = |framework| component_source(framework, "mdi:arrow-left", 24, 16, BODY);
+
+        let react = source(Framework::React);
+        assert!(react.contains("export default function MdiArrowLeft({"));
+        assert!(react.contains(r#"viewBox="0 0 24 16""#));
+        assert!(react.contains(r#"const body = "<path d=\"M0 0h24\"/><style>{fill:red}</style>";"#));
+
+        let solid = source(Framework::Solid);
+        assert!(solid.contains(r#"const body = "<path d=\"M0 0h24\"/><style>{fill:red}</style>";"#));
+
+        let vue = source(Framework::Vue);
+        assert!(vue.contains(&format!("<g v-pre>{}</g>", BODY)));
+
+        // Braces in the body would otherwise be Svelte expressions.
+        let svelte = source(Framework::Svelte);
+        assert!(svelte.contains(r#"<path d="M0 0h24"/><style>&#123;fill:red&#125;</style>"#));
+
+        let web_component = source(Framework::WebComponent);
+        assert!(web_component.contains(r#"customElements.define("mdi-arrow-left", MdiArrowLeft);"#));
+    }
+}
@@ -1,28 +1,183 @@
 use std::{
+    collections::HashMap,
     error::Error,
     fs::{self, create_dir_all, File},
     io::{BufReader, BufWriter, Read, Write},
+    path::PathBuf,
 };
 
 use serde::Deserialize;
 
-use crate::files::get_home_dir;
+use crate::{
+    cli::{BrowseArgs, QueryArgs},
+    files::get_home_dir,
+    template::{preset, PRESETS},
+};
+
+/// The public Iconify API, used for remote searches when no other API is configured.
+pub const DEFAULT_SEARCH_API: &str = "https://api.iconify.design";
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 pub struct Config {
     pub default_browse: Option<bool>,
     pub custom_output: Option<String>,
+    pub remote_search: Option<bool>,
+    pub search_api: Option<String>,
+    pub searches: Option<HashMap<String, SavedSearch>>,
+    pub templates: Option<HashMap<String, String>>,
+    pub default_template: Option<String>,
+}
+
+/// The name `custom_output` is listed under among the output templates.
+pub const CUSTOM_OUTPUT_TEMPLATE: &str = "custom_output";
+
+impl Config {
+    /// The names of the templates defined in the config, sorted. A non-empty
+    /// `custom_output` is included as a template of its own.
+    pub fn configured_template_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .templates
+            .iter()
+            .flat_map(|templates| templates.keys().cloned())
+            .collect();
+        names.sort();
+
+        if self
+            .custom_output
+            .as_ref()
+            .is_some_and(|output| !output.is_empty())
+            && !names.iter().any(|name| name == CUSTOM_OUTPUT_TEMPLATE)
+        {
+            names.insert(0, CUSTOM_OUTPUT_TEMPLATE.to_string());
+        }
+
+        names
+    }
+
+    /// The names of all output templates: the configured ones, followed by the
+    /// built-in presets they don't override.
+    pub fn template_names(&self) -> Vec<String> {
+        let mut names = self.configured_template_names();
+
+        for (preset, _) in PRESETS {
+            if !names.iter().any(|name| name == preset) {
+                names.push(preset.to_string());
+            }
+        }
+
+        names
+    }
+
+    /// Returns the template of the given name, from the config or the presets.
+    pub fn template(&self, name: &str) -> Option<&str> {
+        self.templates
+            .as_ref()
+            .and_then(|templates| templates.get(name))
+            .or(self
+                .custom_output
+                .as_ref()
+                .filter(|_| name == CUSTOM_OUTPUT_TEMPLATE))
+            .map(String::as_str)
+            .or_else(|| preset(name))
+            .filter(|template| !template.is_empty())
+    }
+
+    /// Returns the saved search of the given name, used as `@name` in queries.
+    pub fn saved_search(&self, name: &str) -> Option<&SavedSearch> {
+        self.searches.as_ref()?.get(name)
+    }
+}
+
+/// A named search, used as `@name` in queries. Either just a query string, or
+/// a query and/or a comma separated list of collection prefixes.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum SavedSearch {
+    Query(String),
+    Search {
+        query: Option<String>,
+        prefix: Option<String>,
+    },
+}
+
+impl SavedSearch {
+    pub fn query(&self) -> Option<&str> {
+        match self {
+            SavedSearch::Query(query) => Some(query.as_str()),
+            SavedSearch::Search { query, .. } => query.as_deref(),
+        }
+        .filter(|query| !query.trim().is_empty())
+    }
+
+    pub fn prefix(&self) -> Option<&str> {
+        match self {
+            SavedSearch::Query(_) => None,
+            SavedSearch::Search { prefix, .. } => prefix.as_deref(),
+        }
+    }
+}
+
+/// Returns the name of the output template to use: the --template option, then
+/// the default_template setting, then `custom_output` or the first configured
+/// template. Presets are only used when picked by name.
+pub fn get_template_name(
+    args: &BrowseArgs,
+    config: &Config,
+) -> Result<Option<String>, Box<dyn Error>> {
+    let names = config.template_names();
+
+    let name = match args.template.as_ref().or(config.default_template.as_ref()) {
+        Some(name) if config.template(name).is_none() => {
+            return Err(format!(
+                "Unknown output template '{}'. Available templates: {}.",
+                name,
+                names.join(", ")
+            )
+            .into())
+        }
+        Some(name) => Some(name.clone()),
+        None => config.configured_template_names().first().cloned(),
+    };
+
+    Ok(name)
+}
+
+/// Returns the API to search through, or `None` if the local cache should be searched.
+pub fn get_search_api(args: &QueryArgs, config: &Config) -> Option<String> {
+    let remote = (args.remote || config.remote_search.unwrap_or(false)) && !args.no_remote;
+
+    if !remote {
+        return None;
+    }
+
+    args.search_api
+        .clone()
+        .or_else(|| config.search_api.clone())
+        .or_else(|| Some(DEFAULT_SEARCH_API.to_string()))
+        .map(|api| api.trim_end_matches('/').to_string())
+}
+
+/// The path of the config file, ~/.config/iconify-rs/iconify-rs.toml.
+pub fn config_path() -> PathBuf {
+    get_home_dir().join(".config/iconify-rs/iconify-rs.toml")
+}
+
+/// The directory the collections, icon lists and hashes are cached in,
+/// ~/.local/share/icon-rs/cache.
+pub fn cache_dir() -> PathBuf {
+    get_home_dir().join(icon_core::lookup::CACHE_DIR)
 }
 
 pub fn create_default_config_file() -> Result<bool, Box<dyn Error>> {
-    let home_dir = get_home_dir();
-    let path = home_dir.join(".config/iconify-rs/");
+    let path = config_path();
 
-    create_dir_all(&path.as_path())?;
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)?;
+    }
 
     let default_config = include_str!("../config-default.toml");
 
-    let file = File::create(path.join("iconify-rs.toml"))?;
+    let file = File::create(path)?;
     let mut writer = BufWriter::new(file);
 
     writer.write_all(default_config.as_bytes())?;
@@ -31,9 +186,9 @@ pub fn create_default_config_file() -> Result<bool, Box<dyn Error>> {
 }
 
 pub fn read_config_file() -> Result<Config, Box<dyn Error>> {
-    let file_path = get_home_dir().join(".config/iconify-rs/iconify-rs.toml");
+    let file_path = config_path();
 
-    if !fs::metadata(file_path.clone()).is_ok() {
+    if fs::metadata(file_path.clone()).is_err() {
         create_default_config_file()?;
     }
 
@@ -0,0 +1,178 @@
+use std::{collections::HashMap, error::Error};
+
+use crate::{
+    cli::{CssArgs, CssNaming},
+    enums::IconCollection,
+    export::read_icon_ids,
+    files::{get_icon_xml, write_file},
+    svg::{scale_dimension, standalone_svg, svg_data_uri, SvgOptions},
+};
+
+/// The class name of an icon in a naming style.
+fn class_name(naming: CssNaming, icon: &str) -> Result<String, Box<dyn Error>> {
+    let (prefix, name) = icon
+        .split_once(':')
+        .ok_or(format!("Invalid icon identifier '{}'.", icon))?;
+
+    Ok(match naming {
+        CssNaming::Dash => format!("i-{}-{}", prefix, name),
+        CssNaming::Bracket => format!("icon-[{}--{}]", prefix, name),
+    })
+}
+
+/// Escapes the characters of a class name that aren't allowed in a selector.
+fn escape_selector(class: &str) -> String {
+    let mut result = String::with_capacity(class.len());
+
+    for c in class.chars() {
+        if !(c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            result.push('\\');
+        }
+        result.push(c);
+    }
+
+    result
+}
+
+/// Generates a rule for an icon. Monochrome icons are drawn as a mask over the
+/// text colour, palette icons as a background image with their own colours.
+fn icon_rule(
+    class: &str,
+    size: &str,
+    width: usize,
+    height: usize,
+    body: &str,
+    palette: bool,
+) -> String {
+    let options = SvgOptions {
+        color: "#000".to_string(),
+        ..Default::default()
+    };
+    let uri = svg_data_uri(&standalone_svg(width, height, body, &options));
+
+    let dimensions = format!(
+        "  display: inline-block;\n  width: {};\n  height: {};\n",
+        scale_dimension(size, width as f32 / height as f32),
+        size
+    );
+
+    if palette {
+        format!(
+            ".{} {{\n{}  background: url(\"{}\") no-repeat;\n  background-size: 100% 100%;\n  background-color: transparent;\n}}\n",
+            escape_selector(class),
+            dimensions,
+            uri
+        )
+    } else {
+        format!(
+            ".{} {{\n{}  --svg: url(\"{}\");\n  background-color: currentColor;\n  -webkit-mask-image: var(--svg);\n  mask-image: var(--svg);\n  -webkit-mask-repeat: no-repeat;\n  mask-repeat: no-repeat;\n  -webkit-mask-size: 100% 100%;\n  mask-size: 100% 100%;\n}}\n",
+            escape_selector(class),
+            dimensions,
+            uri
+        )
+    }
+}
+
+/// Generates a stylesheet with a class per icon, with the icons inlined as data URIs.
+pub fn css(
+    args: &CssArgs,
+    collections_cache: &mut HashMap<String, IconCollection>,
+) -> Result<(), Box<dyn Error>> {
+    let icons = read_icon_ids(&args.icons, args.input.as_deref())?;
+
+    let mut rules = Vec::<String>::new();
+    for icon in &icons {
+        let (width, height, body) = get_icon_xml(icon, collections_cache, None)
+            .map_err(|e| format!("Could not read {}: {}", icon, e))?;
+
+        // Icons drawn in the text colour are monochrome, even in palette collections.
+        let palette = icon
+            .split_once(':')
+            .and_then(|(prefix, _)| collections_cache.get(prefix))
+            .is_some_and(|collection| collection.info.palette)
+            && !body.contains("currentColor");
+
+        rules.push(icon_rule(
+            &class_name(args.naming, icon)?,
+            &args.size,
+            width,
+            height,
+            &body,
+            palette,
+        ));
+    }
+
+    let stylesheet = rules.join("\n");
+    match &args.out {
+        Some(path) => {
+            write_file(path, stylesheet.as_bytes())?;
+            println!("{}", path.display());
+        }
+        None => print!("{}", stylesheet),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn data_uris_escape_quotes_and_hashes() {
+        assert_eq!(
+            svg_data_uri(r##"<svg  fill="#fff">'50%'</svg>"##),
+            "data:image/svg+xml,%3Csvg fill='%23fff'%3E%2750%25%27%3C/svg%3E"
+        );
+        assert_eq!(svg_data_uri("é"), "data:image/svg+xml,%C3%A9");
+    }
+
+    #[test]
+    fn class_names_are_escaped_in_selectors() {
+        let dash = class_name(CssNaming::Dash, "mdi:arrow-left").unwrap();
+        let bracket = class_name(CssNaming::Bracket, "mdi:arrow-left").unwrap();
+
+        assert_eq!(escape_selector(&dash), "i-mdi-arrow-left");
+        assert_eq!(escape_selector(&bracket), r"icon-\[mdi--arrow-left\]");
+        assert!(class_name(CssNaming::Dash, "arrow-left").is_err());
+    }
+
+    #[test]
+    fn monochrome_icons_are_masks() {
+        let rule = icon_rule(
+            "i-mdi-home",
+            "1em",
+            32,
+            16,
+            r#"<path fill="currentColor"/>"#,
+            false,
+        );
+
+        assert!(rule.starts_with(
+            ".i-mdi-home {\n  display: inline-block;\n  width: 2em;\n  height: 1em;\n"
+        ));
+        assert!(rule.contains("  --svg: url(\"data:image/svg+xml,"));
+        assert!(rule.contains("fill='%23000'"));
+        assert!(rule.contains("  background-color: currentColor;\n"));
+        assert!(rule.contains("  mask-image: var(--svg);\n"));
+        assert!(!rule.contains("background: url("));
+    }
+
+    #[test]
+    fn palette_icons_are_backgrounds() {
+        let rule = icon_rule(
+            "i-logos-rust",
+            "24px",
+            24,
+            24,
+            r##"<path fill="#f00"/>"##,
+            true,
+        );
+
+        assert!(rule.contains("  width: 24px;\n  height: 24px;\n"));
+        assert!(rule.contains("  background: url(\"data:image/svg+xml,"));
+        assert!(rule.contains("fill='%23f00'"));
+        assert!(rule.contains("  background-color: transparent;\n"));
+        assert!(!rule.contains("mask"));
+    }
+}
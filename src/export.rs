@@ -0,0 +1,142 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs::{create_dir_all, File},
+    io::{stdin, BufRead, BufReader, IsTerminal},
+};
+
+use image::ImageFormat;
+use log::info;
+use resvg::usvg::fontdb::Database;
+
+use crate::{
+    cli::{ExportArgs, RasterArgs, RasterFormat},
+    enums::IconCollection,
+    files::{get_icon_xml, write_file},
+    render::{render_icon, save_pixmap, RasterOptions},
+    svg::{standalone_svg, SvgOptions},
+};
+
+/// Fills in the `{prefix}` and `{name}` placeholders of a filename template.
+pub fn icon_filename(template: &str, icon: &str) -> Result<String, Box<dyn Error>> {
+    let (prefix, name) = icon
+        .split_once(':')
+        .ok_or(format!("Invalid icon identifier '{}'.", icon))?;
+
+    Ok(template.replace("{prefix}", prefix).replace("{name}", name))
+}
+
+/// Collects the icon identifiers from the arguments and the input file, or from
+/// stdin when it is piped. Lines may hold several identifiers separated by
+/// whitespace or commas. Duplicates are dropped, keeping the first occurrence.
+pub fn read_icon_ids(icons: &[String], input: Option<&str>) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut lines = icons.to_vec();
+
+    match input {
+        Some("-") => lines.extend(stdin().lock().lines().map_while(Result::ok)),
+        Some(path) => lines.extend(
+            BufReader::new(File::open(path)?)
+                .lines()
+                .map_while(Result::ok),
+        ),
+        None if icons.is_empty() && !stdin().is_terminal() => {
+            lines.extend(stdin().lock().lines().map_while(Result::ok))
+        }
+        None => {}
+    }
+
+    let mut result = Vec::<String>::new();
+    for id in lines
+        .iter()
+        .flat_map(|line| line.split(|c: char| c.is_whitespace() || c == ','))
+        .filter(|id| !id.is_empty())
+    {
+        if !result.iter().any(|existing| existing == id) {
+            result.push(id.to_string());
+        }
+    }
+
+    if result.is_empty() {
+        return Err("No icons given, pass them as arguments, with --input or on stdin.".into());
+    }
+
+    Ok(result)
+}
+
+/// Writes a standalone SVG file for each of the icons.
+pub fn export(
+    args: &ExportArgs,
+    collections_cache: &mut HashMap<String, IconCollection>,
+) -> Result<(), Box<dyn Error>> {
+    let options = SvgOptions {
+        size: args.size.clone(),
+        color: args.color.clone(),
+        stroke_width: args.stroke_width.clone(),
+    };
+
+    for icon in &args.icons {
+        let (width, height, body) = get_icon_xml(icon, collections_cache, None)?;
+        let svg = standalone_svg(width, height, &body, &options);
+
+        let path = args.out_dir.join(icon_filename(&args.filename, icon)?);
+        write_file(&path, svg.as_bytes())?;
+
+        info!("Exported {} to {}", icon, path.display());
+        println!("{}", path.display());
+    }
+
+    Ok(())
+}
+
+/// Writes images of each of the icons, for every combination of size and density.
+pub fn raster(
+    args: &RasterArgs,
+    collections_cache: &mut HashMap<String, IconCollection>,
+    fontdb: &Database,
+) -> Result<(), Box<dyn Error>> {
+    let options = RasterOptions {
+        color: args.color.clone(),
+        padding: args.padding,
+        background: args.background.clone(),
+        ..Default::default()
+    };
+
+    let (format, extension) = match args.format {
+        RasterFormat::Png => (ImageFormat::Png, "png"),
+        RasterFormat::Webp => (ImageFormat::WebP, "webp"),
+    };
+
+    for icon in &args.icons {
+        let (width, height, body) = get_icon_xml(icon, collections_cache, None)?;
+
+        for size in &args.sizes {
+            for scale in &args.scales {
+                let pixels = size * scale;
+                let padding = options.padding.to_pixels(*size as f32, *scale as f32);
+                let pixmap = render_icon(width, height, &body, pixels, padding, &options, fontdb)?;
+
+                let scale_suffix = if *scale == 1 {
+                    String::new()
+                } else {
+                    format!("@{}x", scale)
+                };
+                let filename = icon_filename(&args.filename, icon)?
+                    .replace("{size}", &size.to_string())
+                    .replace("{scale}", &scale_suffix)
+                    .replace("{ext}", extension);
+
+                let path = args.out_dir.join(filename);
+                if let Some(parent) = path.parent() {
+                    create_dir_all(parent)?;
+                }
+
+                save_pixmap(&pixmap, &path, format)?;
+
+                info!("Exported {} to {}", icon, path.display());
+                println!("{}", path.display());
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,243 @@
+use std::{collections::HashMap, error::Error, fs::create_dir_all};
+
+use image::{
+    codecs::ico::{IcoEncoder, IcoFrame},
+    ExtendedColorType, ImageFormat,
+};
+use resvg::usvg::fontdb::Database;
+
+use crate::{
+    cli::FaviconArgs,
+    enums::IconCollection,
+    files::{get_icon_xml, write_file},
+    render::{icon_on_canvas, pixmap_to_image, render_icon, save_pixmap, RasterOptions},
+};
+
+/// The sizes bundled in favicon.ico.
+const ICO_SIZES: [u32; 3] = [16, 32, 48];
+
+/// The sizes required by PWA manifests.
+const MANIFEST_SIZES: [u32; 2] = [192, 512];
+
+const APPLE_TOUCH_SIZE: u32 = 180;
+
+/// The size of the canvas of the SVG favicon. Padding in pixels is relative to it.
+const SVG_SIZE: u32 = 32;
+
+/// Writes a multi-resolution favicon.ico, the PNG icons used by PWA manifests and
+/// iOS, an SVG favicon and a web manifest, then prints the HTML to link them.
+pub fn favicon(
+    args: &FaviconArgs,
+    collections_cache: &mut HashMap<String, IconCollection>,
+    fontdb: &Database,
+) -> Result<(), Box<dyn Error>> {
+    let (width, height, body) = get_icon_xml(&args.icon, collections_cache, None)?;
+
+    let options = RasterOptions {
+        color: args.color.clone(),
+        padding: args.padding,
+        background: args.background.clone(),
+        corner_radius: args.corner_radius,
+    };
+
+    create_dir_all(&args.out_dir)?;
+
+    write_file(
+        &args.out_dir.join("favicon.ico"),
+        &ico(width, height, &body, &options, fontdb)?,
+    )?;
+
+    for size in MANIFEST_SIZES {
+        let padding = options.padding.to_pixels(size as f32, 1.0);
+        let pixmap = render_icon(width, height, &body, size, padding, &options, fontdb)?;
+        save_pixmap(
+            &pixmap,
+            &args.out_dir.join(format!("icon-{}.png", size)),
+            ImageFormat::Png,
+        )?;
+    }
+
+    // iOS shows transparent touch icons on black, so they always get a background.
+    let apple_options = RasterOptions {
+        background: Some(args.background.clone().unwrap_or("#fff".to_string())),
+        corner_radius: 0.0,
+        ..options.clone()
+    };
+    let padding = apple_options
+        .padding
+        .to_pixels(APPLE_TOUCH_SIZE as f32, 1.0);
+    let pixmap = render_icon(
+        width,
+        height,
+        &body,
+        APPLE_TOUCH_SIZE,
+        padding,
+        &apple_options,
+        fontdb,
+    )?;
+    save_pixmap(
+        &pixmap,
+        &args.out_dir.join("apple-touch-icon.png"),
+        ImageFormat::Png,
+    )?;
+
+    let padding = options.padding.to_pixels(SVG_SIZE as f32, 1.0);
+    let svg = icon_on_canvas(width, height, &body, SVG_SIZE, padding, &options);
+    write_file(&args.out_dir.join("favicon.svg"), svg.as_bytes())?;
+
+    let name = match &args.name {
+        Some(name) => name.to_string(),
+        None => args.icon.rsplit(':').next().unwrap_or_default().to_string(),
+    };
+    let theme_color = args
+        .theme_color
+        .clone()
+        .or(args.background.clone())
+        .unwrap_or("#ffffff".to_string());
+    let base_path = format!("{}/", args.base_path.trim_end_matches('/'));
+
+    write_file(
+        &args.out_dir.join("site.webmanifest"),
+        manifest(&name, &theme_color, &base_path)?.as_bytes(),
+    )?;
+
+    println!("{}", html_snippet(&theme_color, &base_path));
+
+    Ok(())
+}
+
+/// Renders the icon at each of the ICO sizes and bundles the images in an ICO file.
+fn ico(
+    width: usize,
+    height: usize,
+    body: &str,
+    options: &RasterOptions,
+    fontdb: &Database,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut frames = Vec::new();
+    for size in ICO_SIZES {
+        let padding = options.padding.to_pixels(size as f32, 1.0);
+        let pixmap = render_icon(width, height, body, size, padding, options, fontdb)?;
+        let image = pixmap_to_image(&pixmap);
+
+        frames.push(IcoFrame::as_png(
+            image.as_raw(),
+            size,
+            size,
+            ExtendedColorType::Rgba8,
+        )?);
+    }
+
+    let mut ico = Vec::new();
+    IcoEncoder::new(&mut ico).encode_images(&frames)?;
+
+    Ok(ico)
+}
+
+/// Builds the web manifest listing the PWA icons.
+fn manifest(name: &str, theme_color: &str, base_path: &str) -> Result<String, Box<dyn Error>> {
+    let icons: Vec<serde_json::Value> = MANIFEST_SIZES
+        .iter()
+        .map(|size| {
+            serde_json::json!({
+                "src": format!("{}icon-{}.png", base_path, size),
+                "sizes": format!("{0}x{0}", size),
+                "type": "image/png",
+            })
+        })
+        .collect();
+
+    let manifest = serde_json::json!({
+        "name": name,
+        "short_name": name,
+        "icons": icons,
+        "theme_color": theme_color,
+        "background_color": theme_color,
+        "display": "standalone",
+    });
+
+    Ok(serde_json::to_string_pretty(&manifest)?)
+}
+
+/// Builds the tags that link the favicons and manifest from the head of a page.
+fn html_snippet(theme_color: &str, base_path: &str) -> String {
+    [
+        format!(
+            r#"<link rel="icon" href="{}favicon.ico" sizes="48x48">"#,
+            base_path
+        ),
+        format!(
+            r#"<link rel="icon" href="{}favicon.svg" type="image/svg+xml">"#,
+            base_path
+        ),
+        format!(
+            r#"<link rel="apple-touch-icon" href="{}apple-touch-icon.png">"#,
+            base_path
+        ),
+        format!(
+            r#"<link rel="manifest" href="{}site.webmanifest">"#,
+            base_path
+        ),
+        format!(r#"<meta name="theme-color" content="{}">"#, theme_color),
+    ]
+    .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ico_holds_an_image_per_size() {
+        let ico = ico(
+            24,
+            24,
+            r#"<path d="M0 0h24v24H0z"/>"#,
+            &RasterOptions::default(),
+            &Database::new(),
+        )
+        .unwrap();
+
+        // The directory follows the 6 byte header, with an entry of 16 bytes per
+        // image that starts with its width and height, where 0 means 256.
+        let count = u16::from_le_bytes([ico[4], ico[5]]) as usize;
+        let sizes: Vec<(u8, u8)> = (0..count)
+            .map(|i| (ico[6 + 16 * i], ico[7 + 16 * i]))
+            .collect();
+
+        assert_eq!(sizes, vec![(16, 16), (32, 32), (48, 48)]);
+    }
+
+    #[test]
+    fn manifest_lists_the_pwa_icons() {
+        let manifest: serde_json::Value =
+            serde_json::from_str(&manifest("home", "#123456", "/icons/").unwrap()).unwrap();
+
+        assert_eq!(
+            manifest,
+            serde_json::json!({
+                "name": "home",
+                "short_name": "home",
+                "icons": [
+                    { "src": "/icons/icon-192.png", "sizes": "192x192", "type": "image/png" },
+                    { "src": "/icons/icon-512.png", "sizes": "512x512", "type": "image/png" },
+                ],
+                "theme_color": "#123456",
+                "background_color": "#123456",
+                "display": "standalone",
+            })
+        );
+    }
+
+    #[test]
+    fn html_links_every_file() {
+        assert_eq!(
+            html_snippet("#123456", "/"),
+            r##"<link rel="icon" href="/favicon.ico" sizes="48x48">
+<link rel="icon" href="/favicon.svg" type="image/svg+xml">
+<link rel="apple-touch-icon" href="/apple-touch-icon.png">
+<link rel="manifest" href="/site.webmanifest">
+<meta name="theme-color" content="#123456">"##
+        );
+    }
+}
@@ -1,9 +1,15 @@
-use crate::enums::{Collection, IconCollection};
+use crate::config::cache_dir;
+use crate::enums::{Alias, Collection, Icon, IconCollection, Themes};
 use crate::parser;
-use log::info;
+use crate::similar::{self, IconHash};
+use crate::traits::IconTraits;
+pub use icon_core::get_collection;
+use icon_core::icon_data;
+use log::{info, warn};
 use resvg::tiny_skia;
 use resvg::usvg::fontdb::Database;
 use resvg::usvg::{self};
+use serde::Deserialize;
 use std::collections::HashMap;
 use std::error::Error;
 use std::io::copy;
@@ -11,77 +17,212 @@ use viuer::{print_from_file, Config};
 
 use std::fs::{create_dir_all, File};
 use std::io::{BufRead, BufReader, BufWriter, Write};
-use std::{path::PathBuf, process::exit};
+use std::{
+    path::{Path, PathBuf},
+    process::exit,
+};
 
 use home::home_dir;
 use log::error;
 
+/// Icons of a collection as returned by the API, without the collection info.
+#[derive(Deserialize)]
+struct ApiIcons {
+    width: Option<usize>,
+    height: Option<usize>,
+    icons: HashMap<String, Icon>,
+    aliases: Option<HashMap<String, Alias>>,
+}
+
+/// Fetches the info of a collection from the API, without any of its icons.
+fn fetch_api_collection(api: &str, collection_id: &str) -> Result<IconCollection, Box<dyn Error>> {
+    let response =
+        reqwest::blocking::get(format!("{}/collections?prefixes={}", api, collection_id))?
+            .error_for_status()?
+            .text()?;
+
+    let mut collections: HashMap<String, Collection> = serde_json::from_str(&response)?;
+    let info = collections
+        .remove(collection_id)
+        .ok_or(format!("Unknown collection '{}'.", collection_id))?;
+
+    Ok(IconCollection {
+        prefix: collection_id.to_string(),
+        width: info.height,
+        height: info.height,
+        prefixes: None,
+        suffixes: None,
+        chars: None,
+        last_modified: 0,
+        info,
+        icons: HashMap::new(),
+        aliases: None,
+        categories: None,
+    })
+}
+
+/// The most icons asked for in one request to the API, which keeps the URL short.
+const API_ICONS_PER_REQUEST: usize = 100;
+
+/// Fetches icons from the API and adds them to the collection.
+fn request_api_icons(
+    api: &str,
+    collection: &mut IconCollection,
+    names: &[&str],
+) -> Result<(), Box<dyn Error>> {
+    info!(
+        "Fetching {} icons of {} from {}..",
+        names.len(),
+        collection.prefix,
+        api
+    );
+    let response = reqwest::blocking::get(format!(
+        "{}/{}.json?icons={}",
+        api,
+        collection.prefix,
+        names.join(",")
+    ))?
+    .error_for_status()?
+    .text()?;
+
+    let icons: ApiIcons = serde_json::from_str(&response)?;
+
+    // The API gives the default size of its response, which may differ from the
+    // size the collection was created with.
+    for (name, mut icon) in icons.icons {
+        icon.width = icon.width.or(icons.width);
+        icon.height = icon.height.or(icons.height);
+        collection.icons.insert(name, icon);
+    }
+    collection
+        .aliases
+        .get_or_insert_with(HashMap::new)
+        .extend(icons.aliases.unwrap_or_default());
+
+    Ok(())
+}
+
+/// Puts a collection in the cache, unless it is in there already. With an icon
+/// API, collections that aren't on disk are created from their info only, and
+/// their icons are fetched from the API as they are needed.
+fn cache_collection<'a>(
+    collections_cache: &'a mut HashMap<String, IconCollection>,
+    collection_id: &str,
+    api: Option<&str>,
+) -> Result<&'a mut IconCollection, Box<dyn Error>> {
+    if !collections_cache.contains_key(collection_id) {
+        let collection = match api {
+            Some(api) if get_collection(collection_id).is_err() => {
+                fetch_api_collection(api, collection_id).or_else(|e| {
+                    eprintln!("Could not fetch '{}' from {}: {}", collection_id, api, e);
+                    fetch_icons_in_collection(collection_id)
+                })?
+            }
+            _ => fetch_icons_in_collection(collection_id)?,
+        };
+        collections_cache.insert(collection_id.to_string(), collection);
+    }
+
+    Ok(collections_cache
+        .get_mut(collection_id)
+        .expect("collection was cached"))
+}
+
+/// Fetches the icons that aren't cached yet from the API, with a request per
+/// collection rather than per icon.
+pub fn fetch_api_icons(
+    api: &str,
+    icons: &[String],
+    collections_cache: &mut HashMap<String, IconCollection>,
+) -> Result<(), Box<dyn Error>> {
+    let mut names = HashMap::<&str, Vec<&str>>::new();
+    for icon in icons {
+        if let Some((collection_id, name)) = icon.split_once(':') {
+            names.entry(collection_id).or_default().push(name);
+        }
+    }
+
+    for (collection_id, names) in names {
+        let collection = cache_collection(collections_cache, collection_id, Some(api))?;
+        let missing: Vec<&str> = names
+            .into_iter()
+            .filter(|name| icon_data(collection, name).is_none())
+            .collect();
+
+        for names in missing.chunks(API_ICONS_PER_REQUEST) {
+            request_api_icons(api, collection, names)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Looks up the size and body of an icon, fetching its collection if needed.
+/// With an icon API, which is used when searching remotely, collections that
+/// aren't cached are fetched from it one icon at a time instead.
 pub fn get_icon_xml(
     icon_identifier: &str,
     collections_cache: &mut HashMap<String, IconCollection>,
+    api: Option<&str>,
 ) -> Result<(usize, usize, String), Box<dyn Error>> {
-    let Some((collection_id, icon_identifier)) = icon_identifier.split_once(':') else {
-        todo!();
+    let Some((collection_id, name)) = icon_identifier.split_once(':') else {
+        return Err(format!("Invalid icon identifier '{}'.", icon_identifier).into());
     };
 
-    if let Some(collection) = collections_cache.get(collection_id) {
-        if let Some(icon) = collection.icons.get(icon_identifier) {
-            Ok((
-                collection.width.unwrap_or(16),
-                collection.height.unwrap_or(16),
-                icon.body.clone(),
-            ))
-        } else {
-            Err("Could not find icon.".into())
-        }
-    } else {
-        let collection = get_collection(collection_id)?;
-        collections_cache.insert(collection_id.to_string(), collection.clone());
-
-        if let Some(icon) = collection.icons.get(icon_identifier) {
-            Ok((
-                collection.width.unwrap_or(16),
-                collection.height.unwrap_or(16),
-                icon.body.clone(),
-            ))
-        } else {
-            Err("Could not find icon.".into())
+    let collection = cache_collection(collections_cache, collection_id, api)?;
+    if let Some(api) = api {
+        if icon_data(collection, name).is_none() {
+            request_api_icons(api, collection, &[name])?;
         }
     }
-}
 
-pub fn preview(
-    icon_identifier: &str,
-    collections_cache: &mut HashMap<String, IconCollection>,
-    fontdb: &mut Database,
-) -> Result<(), Box<dyn Error>> {
-    let mut file = Vec::new();
+    let Some(icon) = icon_data(collection, name) else {
+        return Err(format!("Unknown icon '{}'.", icon_identifier).into());
+    };
 
-    let (width, height, xml) = get_icon_xml(icon_identifier, collections_cache)?;
+    Ok((icon.width, icon.height, icon.body))
+}
 
+/// Wraps an icon body in an SVG document, drawn in white at the given size for
+/// dark terminal backgrounds.
+pub fn icon_svg_document(width: usize, height: usize, body: &str, size: u32) -> String {
     let header = format!(
-        r#"<svg xmlns="http://www.w3.org/2000/svg" width="96" height="96" color="white" viewBox="0 0 {} {}">"#,
-        width, height
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" color="white" viewBox="0 0 {} {}">"#,
+        size, size, width, height
     );
     let footer = r#"</svg>"#;
 
-    let xml = xml.replace("stroke=\"#000\"", "stroke=\"#fff\"");
+    let body = body.replace("stroke=\"#000\"", "stroke=\"#fff\"");
 
-    let _ = file.write_all(header.as_bytes());
-    let _ = file.write_all(xml.as_bytes());
-    let _ = file.write_all(footer.as_bytes());
-
-    let in_file = file.as_slice();
-    let out_file = "/tmp/icon-rs-preview.png";
+    format!("{}{}{}", header, body, footer)
+}
 
+pub fn render_svg(data: &[u8], fontdb: &Database) -> Result<tiny_skia::Pixmap, Box<dyn Error>> {
     let tree = {
         let opt = usvg::Options::default();
-        usvg::Tree::from_data(&in_file, &opt, &fontdb).unwrap()
+        usvg::Tree::from_data(data, &opt, fontdb)?
     };
 
     let pixmap_size = tree.size().to_int_size();
-    let mut pixmap = tiny_skia::Pixmap::new(pixmap_size.width(), pixmap_size.height()).unwrap();
+    let mut pixmap = tiny_skia::Pixmap::new(pixmap_size.width(), pixmap_size.height())
+        .ok_or("Could not create pixmap.")?;
     resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+
+    Ok(pixmap)
+}
+
+pub fn preview(
+    icon_identifier: &str,
+    collections_cache: &mut HashMap<String, IconCollection>,
+    api: Option<&str>,
+    fontdb: &mut Database,
+) -> Result<(), Box<dyn Error>> {
+    let (width, height, xml) = get_icon_xml(icon_identifier, collections_cache, api)?;
+
+    let in_file = icon_svg_document(width, height, &xml, 96);
+    let out_file = "/tmp/icon-rs-preview.png";
+
+    let pixmap = render_svg(in_file.as_bytes(), fontdb)?;
     pixmap.save_png(out_file).unwrap();
 
     let conf = Config {
@@ -120,10 +261,9 @@ pub fn fetch_icons_in_collection(collection_id: &str) -> Result<IconCollection,
 
     let response = reqwest::blocking::get(url)?.text()?;
 
-    let home_dir = get_home_dir();
-    let path = home_dir.join(".local/share/icon-rs/cache/collections");
+    let path = cache_dir().join("collections");
 
-    create_dir_all(&path.as_path())?;
+    create_dir_all(path.as_path())?;
 
     let filename = format!("{}.json", collection_id);
     let full_path = path.join(filename);
@@ -137,38 +277,44 @@ pub fn fetch_icons_in_collection(collection_id: &str) -> Result<IconCollection,
     Ok(result)
 }
 
-pub fn write_bytes_to_file_in_home_dir(
-    path: &str,
+pub fn write_bytes_to_file_in_dir(
+    dir: &Path,
     filename: &str,
     data: &[u8],
 ) -> Result<(), Box<dyn Error>> {
-    let home_dir = get_home_dir();
-    let full_path = home_dir.join(path);
+    create_dir_all(dir)?;
 
-    create_dir_all(full_path.clone())?;
-
-    let file_path = full_path.join(filename);
+    let file_path = dir.join(filename);
 
     let mut dest = File::create(&file_path)?;
-    let result = dest.write_all(data)?;
+    dest.write_all(data)?;
 
-    Ok(result)
+    Ok(())
+}
+
+/// Writes a file, creating the directories it is in.
+pub fn write_file(path: &Path, data: &[u8]) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)?;
+    }
+
+    let mut dest = File::create(path)?;
+    dest.write_all(data)?;
+
+    Ok(())
 }
 
-pub fn write_iterator_to_file_in_home_dir<I>(
-    path: &str,
+pub fn write_iterator_to_file_in_dir<I>(
+    dir: &Path,
     filename: &str,
     iterator: I,
 ) -> Result<(), Box<dyn Error>>
 where
     I: IntoIterator<Item = String>,
 {
-    let home_dir = get_home_dir();
-    let full_path = home_dir.join(path);
+    create_dir_all(dir)?;
 
-    create_dir_all(full_path.clone())?;
-
-    let file_path = full_path.join(filename);
+    let file_path = dir.join(filename);
 
     let dest = File::create(&file_path)?;
 
@@ -192,6 +338,12 @@ pub fn fetch_collections(force: bool) -> Result<Vec<String>, Box<dyn Error>> {
         info!("No cached collections found. Fetching collections..");
     };
 
+    Ok(download_collections()?.into_keys().collect())
+}
+
+/// Downloads the details of all collections and caches them, along with the
+/// list of collection ids.
+fn download_collections() -> Result<HashMap<String, Collection>, Box<dyn Error>> {
     info!("Downloading collections..");
     let response = reqwest::blocking::get(
         "https://raw.githubusercontent.com/iconify/icon-sets/master/collections.json",
@@ -199,67 +351,142 @@ pub fn fetch_collections(force: bool) -> Result<Vec<String>, Box<dyn Error>> {
     .text()?;
     info!("Downloaded collections..");
 
-    write_bytes_to_file_in_home_dir(
-        ".local/share/icon-rs/cache",
-        "collections.json",
-        response.as_bytes(),
-    )?;
+    write_bytes_to_file_in_dir(&cache_dir(), "collections.json", response.as_bytes())?;
 
     info!("Parsing collections..");
     let collections: HashMap<String, Collection> = serde_json::from_str(&response)?;
     info!("Parsed collections..");
 
-    let collection_ids: Vec<String> = collections.iter().map(|(k, _)| k.to_string()).collect();
+    let collection_ids: Vec<String> = collections.keys().map(|k| k.to_string()).collect();
 
     info!("Writing collections file..");
-    write_iterator_to_file_in_home_dir(
-        ".local/share/icon-rs/cache",
-        "collection_ids.txt",
-        collection_ids.clone(),
-    )?;
+    write_iterator_to_file_in_dir(&cache_dir(), "collection_ids.txt", collection_ids)?;
     info!("Wrote collections file..");
 
-    Ok(collection_ids)
+    Ok(collections)
 }
 
 pub fn get_collection_ids() -> Result<Vec<String>, Box<dyn Error>> {
-    let file_path = get_home_dir().join(".local/share/icon-rs/cache/collection_ids.txt");
+    let file_path = cache_dir().join("collection_ids.txt");
     let reader = BufReader::new(File::open(file_path)?);
     let mut result = Vec::<String>::new();
 
-    for line in reader.lines().flatten() {
+    for line in reader.lines().map_while(Result::ok) {
         result.push(line);
     }
 
     Ok(result)
 }
 
-pub fn get_collection(collection_id: &str) -> Result<IconCollection, Box<dyn Error>> {
-    let path = get_home_dir().join(format!(
-        ".local/share/icon-rs/cache/collections/{}.json",
-        collection_id
-    ));
+pub fn get_cached_icons() -> Result<Vec<String>, Box<dyn Error>> {
+    let path = cache_dir().join("icons.txt");
+
+    if let Ok(file) = File::open(path) {
+        let reader = BufReader::new(file);
+        let mut result = Vec::<String>::new();
+
+        for line in reader.lines().map_while(Result::ok) {
+            result.push(line);
+        }
+
+        Ok(result)
+    } else {
+        generate_cached_icons()
+    }
+}
+
+/// Reads the details of all collections, fetching them if they aren't cached yet.
+pub fn get_cached_collections() -> Result<HashMap<String, Collection>, Box<dyn Error>> {
+    let path = cache_dir().join("collections.json");
+
+    if let Ok(file) = File::open(&path) {
+        let result: HashMap<String, Collection> = serde_json::from_reader(BufReader::new(file))?;
+
+        Ok(result)
+    } else {
+        download_collections()
+    }
+}
+
+/// Reads the theme definitions of all collections, written when generating the icons cache.
+pub fn get_cached_themes() -> Result<HashMap<String, Themes>, Box<dyn Error>> {
+    let path = cache_dir().join("themes.json");
 
     let reader = BufReader::new(File::open(path)?);
-    let result: IconCollection = serde_json::from_reader(reader)?;
+    let result: HashMap<String, Themes> = serde_json::from_reader(reader)?;
 
     Ok(result)
 }
 
-pub fn get_cached_icons() -> Result<Vec<String>, Box<dyn Error>> {
-    let path = get_home_dir().join(".local/share/icon-rs/cache/icons.txt");
+/// Writes the theme definitions of all collections, read by `get_cached_themes`.
+pub fn write_cached_themes(themes: &HashMap<String, Themes>) -> Result<(), Box<dyn Error>> {
+    write_bytes_to_file_in_dir(
+        &cache_dir(),
+        "themes.json",
+        serde_json::to_string(themes)?.as_bytes(),
+    )
+}
+
+/// Fetches the theme definitions of a collection, from the icon API when one is
+/// given, as that doesn't require downloading the whole collection.
+pub fn fetch_collection_themes(
+    collection_id: &str,
+    api: Option<&str>,
+) -> Result<Themes, Box<dyn Error>> {
+    if let Some(api) = api {
+        let themes = reqwest::blocking::get(format!("{}/collection?prefix={}", api, collection_id))
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.text());
+
+        match themes.map(|themes| serde_json::from_str::<Themes>(&themes)) {
+            Ok(Ok(themes)) => return Ok(themes),
+            Ok(Err(e)) => eprintln!("Could not read the themes of '{}': {}", collection_id, e),
+            Err(e) => eprintln!("Could not fetch the themes of '{}': {}", collection_id, e),
+        }
+    }
+
+    Ok(Themes::from(&fetch_icons_in_collection(collection_id)?))
+}
+
+/// Reads the drawing traits of all icons, written when generating the icons cache.
+pub fn get_cached_traits() -> Result<HashMap<String, IconTraits>, Box<dyn Error>> {
+    let path = cache_dir().join("traits.txt");
 
     if let Ok(file) = File::open(path) {
         let reader = BufReader::new(file);
-        let mut result = Vec::<String>::new();
+        let mut result = HashMap::<String, IconTraits>::new();
 
-        for line in reader.lines().flatten() {
-            result.push(line);
+        for line in reader.lines().map_while(Result::ok) {
+            if let Some((icon, traits)) = line.split_once('\t') {
+                result.insert(icon.to_string(), traits.parse()?);
+            }
         }
 
         Ok(result)
     } else {
-        generate_cached_icons()
+        generate_cached_icons()?;
+        get_cached_traits()
+    }
+}
+
+/// Reads the perceptual hashes of all icons, written when generating the icons cache.
+pub fn get_cached_hashes() -> Result<HashMap<String, IconHash>, Box<dyn Error>> {
+    let path = cache_dir().join("hashes.txt");
+
+    if let Ok(file) = File::open(path) {
+        let reader = BufReader::new(file);
+        let mut result = HashMap::<String, IconHash>::new();
+
+        for line in reader.lines().map_while(Result::ok) {
+            if let Some((icon, hash)) = line.split_once('\t') {
+                result.insert(icon.to_string(), similar::hash_from_hex(hash)?);
+            }
+        }
+
+        Ok(result)
+    } else {
+        generate_cached_icons()?;
+        get_cached_hashes()
     }
 }
 
@@ -268,16 +495,44 @@ pub fn generate_cached_icons() -> Result<Vec<String>, Box<dyn Error>> {
     let collections = fetch_collections(false)?;
 
     let mut icons = Vec::new();
+    let mut themes = HashMap::<String, Themes>::new();
+    let mut traits = Vec::new();
+    let mut hashes = Vec::new();
+    // Icons are hashed by their shapes, so no fonts are needed for the few icons with text.
+    let fontdb = Database::new();
     for collection in collections {
         info!("  - {}", collection);
         let icons_in_collection = fetch_icons_in_collection(&collection)?;
 
-        for (icon, _) in icons_in_collection.icons {
+        themes.insert(collection.clone(), Themes::from(&icons_in_collection));
+
+        for icon in icons_in_collection.icons.keys() {
+            let data = icon_data(&icons_in_collection, icon).expect("icon is in the collection");
+
+            traits.push(format!(
+                "{}:{}\t{}",
+                collection,
+                icon,
+                IconTraits::from_body(&data.body)
+            ));
+            // An icon that can't be rendered is left out of the similarity search.
+            match similar::hash_body(data.width, data.height, &data.body, &fontdb) {
+                Ok(hash) => hashes.push(format!(
+                    "{}:{}\t{}",
+                    collection,
+                    icon,
+                    similar::hash_to_hex(&hash)
+                )),
+                Err(e) => warn!("Could not hash {}:{}: {}", collection, icon, e),
+            }
             icons.push(format!("{}:{}", collection, icon));
         }
     }
 
-    write_iterator_to_file_in_home_dir(".local/share/icon-rs/cache", "icons.txt", icons)?;
+    write_iterator_to_file_in_dir(&cache_dir(), "icons.txt", icons)?;
+    write_iterator_to_file_in_dir(&cache_dir(), "traits.txt", traits)?;
+    write_iterator_to_file_in_dir(&cache_dir(), "hashes.txt", hashes)?;
+    write_cached_themes(&themes)?;
 
     get_cached_icons()
 }
@@ -286,21 +541,27 @@ pub fn query(
     query: &Option<String>,
     prefix: &Option<String>,
 ) -> Result<Vec<String>, Box<dyn Error>> {
+    // Parsed first, so an invalid query is reported before the cache is read.
+    let parsed_query = query.as_deref().map(parser::parse).transpose()?;
+
     let icons = get_cached_icons()?;
 
-    let parsed_query = if let Some(query) = query {
-        Some(parser::parse(query).unwrap())
-    } else {
-        None
+    // The traits are only needed, and loaded, when the query filters on them.
+    let traits = match &parsed_query {
+        Some(parsed_query) if parser::has_filters(parsed_query) => get_cached_traits()?,
+        _ => HashMap::new(),
     };
+    let no_traits = IconTraits::default();
 
     let found: Vec<String> = icons
         .iter()
         .filter(|i| {
             let matching = if let Some(_query) = query {
                 if let Some(parsed_query) = &parsed_query {
-                    parser::match_query(i.to_string(), parsed_query.clone())
+                    let icon_traits = traits.get(i.as_str()).unwrap_or(&no_traits);
+                    parser::match_query(i, icon_traits, parsed_query)
                         .expect("query can be parsed")
+                        .is_some()
                 } else {
                     true
                 }
@@ -308,8 +569,12 @@ pub fn query(
                 true
             };
 
+            // Several collections can be selected by separating their prefixes with commas.
             if let Some(prefix) = &prefix {
-                matching && i.starts_with(&format!("{}:", prefix))
+                matching
+                    && prefix
+                        .split(',')
+                        .any(|prefix| i.starts_with(&format!("{}:", prefix.trim())))
             } else {
                 matching
             }
@@ -319,3 +584,18 @@ pub fn query(
 
     Ok(found)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_rejects_unknown_filter_values() {
+        let error = query(&Some("home style:foo".to_string()), &None).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Unknown value 'foo' for the style filter, expected one of: stroke, fill."
+        );
+    }
+}
@@ -0,0 +1,406 @@
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+};
+
+use resvg::{
+    tiny_skia::{Path, PathSegment, Point},
+    usvg::{self, fontdb::Database, FillRule, Group, Node},
+};
+use serde_json::{Map, Value};
+
+use crate::{
+    cli::FontArgs,
+    enums::IconCollection,
+    export::{icon_filename, read_icon_ids},
+    files::{get_icon_xml, write_file},
+    sfnt::{build_font, FontInfo, Glyph, GlyphPoint},
+};
+
+const UNITS_PER_EM: u16 = 1000;
+const ASCENDER: i16 = 850;
+const DESCENDER: i16 = -150;
+
+/// Collects the outlines of the filled and stroked shapes of an icon and their fill
+/// rules, with strokes converted to filled outlines.
+fn collect_outlines(group: &Group, resolution_scale: f32, outlines: &mut Vec<(Path, FillRule)>) {
+    for node in group.children() {
+        match node {
+            Node::Group(group) => collect_outlines(group, resolution_scale, outlines),
+            Node::Text(text) => collect_outlines(text.flattened(), resolution_scale, outlines),
+            Node::Path(path) => {
+                if path.visibility() != usvg::Visibility::Visible {
+                    continue;
+                }
+
+                if let Some(fill) = path.fill() {
+                    outlines.extend(
+                        path.data()
+                            .clone()
+                            .transform(path.abs_transform())
+                            .map(|data| (data, fill.rule())),
+                    );
+                }
+
+                if let Some(stroke) = path.stroke() {
+                    let stroke = stroke.to_tiny_skia();
+                    let dashed = match &stroke.dash {
+                        Some(dash) => path.data().dash(dash, resolution_scale),
+                        None => Some(path.data().clone()),
+                    };
+
+                    outlines.extend(
+                        dashed
+                            .and_then(|data| data.stroke(&stroke, resolution_scale))
+                            .and_then(|data| data.transform(path.abs_transform()))
+                            .map(|data| (data, FillRule::NonZero)),
+                    );
+                }
+            }
+            Node::Image(_) => {}
+        }
+    }
+}
+
+fn mix(a: Point, b: Point, t: f32) -> Point {
+    Point::from_xy(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t)
+}
+
+/// Approximates a cubic curve with quadratic ones, returning the control and end
+/// point of each. The curve is halved twice, and each quarter is replaced by the
+/// quadratic curve that best matches both of its controls.
+fn cubic_to_quads(p0: Point, p1: Point, p2: Point, p3: Point) -> Vec<(Point, Point)> {
+    let split = |[p0, p1, p2, p3]: [Point; 4]| {
+        let (a, b, c) = (mix(p0, p1, 0.5), mix(p1, p2, 0.5), mix(p2, p3, 0.5));
+        let (d, e) = (mix(a, b, 0.5), mix(b, c, 0.5));
+        let middle = mix(d, e, 0.5);
+
+        [[p0, a, d, middle], [middle, e, c, p3]]
+    };
+
+    split([p0, p1, p2, p3])
+        .into_iter()
+        .flat_map(split)
+        .map(|[p0, p1, p2, p3]| {
+            let control = Point::from_xy(
+                (3.0 * (p1.x + p2.x) - p0.x - p3.x) / 4.0,
+                (3.0 * (p1.y + p2.y) - p0.y - p3.y) / 4.0,
+            );
+            (control, p3)
+        })
+        .collect()
+}
+
+/// Twice the signed area of a contour, positive when it runs counter-clockwise.
+fn signed_area(contour: &[GlyphPoint]) -> i64 {
+    contour
+        .iter()
+        .zip(contour.iter().cycle().skip(1))
+        .map(|(&(x0, y0, _), &(x1, y1, _))| x0 as i64 * y1 as i64 - x1 as i64 * y0 as i64)
+        .sum()
+}
+
+/// Whether a point lies inside a contour, treating its points as a polygon.
+fn contains(contour: &[GlyphPoint], (x, y): (i16, i16)) -> bool {
+    let (x, y) = (x as f64, y as f64);
+    let mut inside = false;
+
+    for (&(x0, y0, _), &(x1, y1, _)) in contour.iter().zip(contour.iter().cycle().skip(1)) {
+        let (x0, y0, x1, y1) = (x0 as f64, y0 as f64, x1 as f64, y1 as f64);
+        if (y0 > y) != (y1 > y) && x < x0 + (y - y0) / (y1 - y0) * (x1 - x0) {
+            inside = !inside;
+        }
+    }
+
+    inside
+}
+
+/// Fonts fill glyphs with the non-zero rule, so contours of an even-odd shape are
+/// turned clockwise or counter-clockwise by how deeply they are nested. Outer
+/// contours run clockwise, as TrueType expects, holes the other way around.
+fn orient_even_odd(contours: &mut [Vec<GlyphPoint>]) {
+    let depths: Vec<usize> = contours
+        .iter()
+        .enumerate()
+        .map(|(i, contour)| {
+            let &(x, y, _) = contour.iter().find(|point| point.2).unwrap_or(&contour[0]);
+            contours
+                .iter()
+                .enumerate()
+                .filter(|&(j, other)| j != i && contains(other, (x, y)))
+                .count()
+        })
+        .collect();
+
+    for (contour, depth) in contours.iter_mut().zip(depths) {
+        let clockwise = signed_area(contour) < 0;
+        if clockwise != (depth % 2 == 0) {
+            contour.reverse();
+        }
+    }
+}
+
+/// Converts an icon to glyph contours in font units, scaled to the em square with
+/// the icon sitting on the descender.
+fn icon_glyph(
+    width: usize,
+    height: usize,
+    body: &str,
+    fontdb: &Database,
+) -> Result<Glyph, Box<dyn Error>> {
+    let svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">{2}</svg>"#,
+        width,
+        height,
+        body.replace("currentColor", "#000")
+    );
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default(), fontdb)?;
+
+    let scale = UNITS_PER_EM as f32 / height as f32;
+    let mut outlines = Vec::new();
+    collect_outlines(tree.root(), scale, &mut outlines);
+
+    let to_font = |point: Point| -> GlyphPoint {
+        (
+            (point.x * scale).round() as i16,
+            (ASCENDER as f32 - point.y * scale).round() as i16,
+            true,
+        )
+    };
+    let off_curve = |point: Point| -> GlyphPoint {
+        let (x, y, _) = to_font(point);
+        (x, y, false)
+    };
+
+    let mut contours = Vec::<Vec<GlyphPoint>>::new();
+    for (outline, rule) in &outlines {
+        let mut outline_contours = Vec::<Vec<GlyphPoint>>::new();
+        let mut contour = Vec::<GlyphPoint>::new();
+        let mut last = Point::zero();
+
+        for segment in outline.segments() {
+            match segment {
+                PathSegment::MoveTo(point) => {
+                    outline_contours.push(std::mem::take(&mut contour));
+                    contour.push(to_font(point));
+                    last = point;
+                }
+                PathSegment::LineTo(point) => {
+                    contour.push(to_font(point));
+                    last = point;
+                }
+                PathSegment::QuadTo(control, point) => {
+                    contour.push(off_curve(control));
+                    contour.push(to_font(point));
+                    last = point;
+                }
+                PathSegment::CubicTo(c1, c2, point) => {
+                    for (control, end) in cubic_to_quads(last, c1, c2, point) {
+                        contour.push(off_curve(control));
+                        contour.push(to_font(end));
+                    }
+                    last = point;
+                }
+                PathSegment::Close => outline_contours.push(std::mem::take(&mut contour)),
+            }
+        }
+        outline_contours.push(contour);
+
+        for contour in &mut outline_contours {
+            // Rounding can make points coincide, and contours are closed implicitly.
+            contour.dedup_by(|a, b| a.0 == b.0 && a.1 == b.1 && a.2 && b.2);
+            if contour.len() > 1 && contour.first() == contour.last() {
+                contour.pop();
+            }
+        }
+        outline_contours.retain(|contour| contour.len() > 2);
+
+        if *rule == FillRule::EvenOdd {
+            orient_even_odd(&mut outline_contours);
+        }
+        contours.extend(outline_contours);
+    }
+
+    Ok(Glyph {
+        advance: (width as f32 * scale).round() as u16,
+        contours,
+    })
+}
+
+/// Builds an icon font from the icons, writing it as TTF and WOFF2 along with a
+/// stylesheet and a JSON map of the codepoints.
+pub fn font(
+    args: &FontArgs,
+    collections_cache: &mut HashMap<String, IconCollection>,
+    fontdb: &Database,
+) -> Result<(), Box<dyn Error>> {
+    let icons = read_icon_ids(&args.icons, args.input.as_deref())?;
+    let start = u32::from_str_radix(args.start.trim_start_matches("U+"), 16)
+        .map_err(|_| format!("Invalid codepoint '{}'.", args.start))?;
+
+    let mut glyphs = Vec::new();
+    let mut codepoints = Vec::<Option<u32>>::new();
+    for icon in &icons {
+        let (width, height, body) = get_icon_xml(icon, collections_cache, None)
+            .map_err(|e| format!("Could not read {}: {}", icon, e))?;
+        glyphs.push(icon_glyph(width, height, &body, fontdb)?);
+
+        codepoints.push(if args.chars {
+            collection_char(icon, collections_cache)
+        } else {
+            None
+        });
+    }
+
+    // Icons without a codepoint of their own get the next free one from the start.
+    let mut used: HashSet<u32> = HashSet::new();
+    for codepoint in &mut codepoints {
+        if codepoint.is_some_and(|codepoint| !used.insert(codepoint)) {
+            *codepoint = None;
+        }
+    }
+    let mut next = start;
+    let codepoints: Vec<u32> = codepoints
+        .into_iter()
+        .map(|codepoint| {
+            codepoint.unwrap_or_else(|| {
+                while used.contains(&next) {
+                    next += 1;
+                }
+                used.insert(next);
+                next
+            })
+        })
+        .collect();
+
+    let cmap: Vec<(u32, u16)> = codepoints
+        .iter()
+        .enumerate()
+        .map(|(i, codepoint)| (*codepoint, i as u16 + 1))
+        .collect();
+
+    let info = FontInfo {
+        family: &args.name,
+        units_per_em: UNITS_PER_EM,
+        ascender: ASCENDER,
+        descender: DESCENDER,
+    };
+    let (ttf, woff2) = build_font(&info, &glyphs, &cmap)?;
+
+    let mut css = format!(
+        r#"@font-face {{
+  font-family: "{0}";
+  src: url("{0}.woff2") format("woff2"), url("{0}.ttf") format("truetype");
+  font-display: block;
+}}
+
+.{0} {{
+  font-family: "{0}" !important;
+  font-style: normal;
+  font-weight: normal;
+  font-variant: normal;
+  line-height: 1;
+  text-transform: none;
+  -webkit-font-smoothing: antialiased;
+  -moz-osx-font-smoothing: grayscale;
+}}
+"#,
+        args.name
+    );
+
+    let mut map = Map::new();
+    for (icon, codepoint) in icons.iter().zip(&codepoints) {
+        css.push_str(&format!(
+            "\n.{}::before {{\n  content: \"\\{:x}\";\n}}\n",
+            icon_filename(&args.class, icon)?,
+            codepoint
+        ));
+        map.insert(icon.to_string(), Value::String(format!("{:x}", codepoint)));
+    }
+
+    let files = [
+        (format!("{}.ttf", args.name), ttf),
+        (format!("{}.woff2", args.name), woff2),
+        (format!("{}.css", args.name), css.into_bytes()),
+        (
+            format!("{}.json", args.name),
+            serde_json::to_string_pretty(&Value::Object(map))?.into_bytes(),
+        ),
+    ];
+    for (filename, data) in files {
+        let path = args.out_dir.join(filename);
+        write_file(&path, &data)?;
+        println!("{}", path.display());
+    }
+
+    Ok(())
+}
+
+/// The codepoint of an icon in the icon font of its collection, if it has one.
+fn collection_char(icon: &str, collections_cache: &HashMap<String, IconCollection>) -> Option<u32> {
+    let (prefix, name) = icon.split_once(':')?;
+    let chars = collections_cache.get(prefix)?.chars.as_ref()?;
+
+    chars
+        .iter()
+        .find(|(_, char_name)| *char_name == name)
+        .and_then(|(codepoint, _)| u32::from_str_radix(codepoint, 16).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glyph(body: &str) -> Glyph {
+        icon_glyph(24, 24, body, &Database::new()).unwrap()
+    }
+
+    fn clockwise(glyph: &Glyph) -> Vec<bool> {
+        glyph
+            .contours
+            .iter()
+            .map(|contour| signed_area(contour) < 0)
+            .collect()
+    }
+
+    #[test]
+    fn even_odd_contours_alternate_by_depth() {
+        let glyph =
+            glyph(r#"<path fill-rule="evenodd" d="M2 2h20v20H2zM6 6h12v12H6zM9 9h6v6H9z"/>"#);
+
+        assert_eq!(clockwise(&glyph), [true, false, true]);
+    }
+
+    #[test]
+    fn even_odd_contours_are_oriented_either_way_round() {
+        // The same squares as above, drawn in the opposite direction.
+        let glyph =
+            glyph(r#"<path fill-rule="evenodd" d="M2 2v20h20V2zM6 6v12h12V6zM9 9v6h6V9z"/>"#);
+
+        assert_eq!(clockwise(&glyph), [true, false, true]);
+    }
+
+    #[test]
+    fn non_zero_contours_keep_their_direction() {
+        let glyph = glyph(r#"<path d="M2 2h20v20H2zM6 6h12v12H6z"/>"#);
+
+        let clockwise = clockwise(&glyph);
+        assert_eq!(clockwise.len(), 2);
+        assert_eq!(clockwise[0], clockwise[1]);
+    }
+
+    #[test]
+    fn glyphs_are_scaled_to_the_em_square() {
+        let glyph = glyph(r#"<path d="M0 0h24v24H0z"/>"#);
+
+        assert_eq!(glyph.advance, UNITS_PER_EM);
+        assert_eq!(glyph.contours.len(), 1);
+        let xs: Vec<i16> = glyph.contours[0].iter().map(|point| point.0).collect();
+        let ys: Vec<i16> = glyph.contours[0].iter().map(|point| point.1).collect();
+        assert_eq!(xs.iter().min().zip(xs.iter().max()), Some((&0, &1000)));
+        assert_eq!(
+            ys.iter().min().zip(ys.iter().max()),
+            Some((&DESCENDER, &ASCENDER))
+        );
+    }
+}
@@ -0,0 +1,296 @@
+use std::{
+    error::Error,
+    fs::{self, create_dir_all, File, OpenOptions},
+    io::{BufRead, BufReader, Read, Seek, SeekFrom, Write},
+    path::PathBuf,
+};
+
+use crate::files::get_home_dir;
+
+/// The number of searches kept in the history.
+const MAX_HISTORY: usize = 1000;
+
+/// The size of the history file at which it is trimmed to `MAX_HISTORY` searches,
+/// so appending doesn't need to count the searches every time.
+const TRIM_HISTORY_BYTES: u64 = 64 * 1024;
+
+fn history_path() -> PathBuf {
+    get_home_dir().join(".local/share/icon-rs/history.txt")
+}
+
+/// Reads the last search in the history file, from the end of the file only.
+fn last_entry(file: &mut File) -> Result<Option<String>, Box<dyn Error>> {
+    let length = file.metadata()?.len();
+    file.seek(SeekFrom::Start(length.saturating_sub(4096)))?;
+
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail)?;
+
+    Ok(String::from_utf8_lossy(&tail)
+        .lines()
+        .next_back()
+        .map(String::from))
+}
+
+/// Appends a search to the history file, unless it repeats the previous search.
+/// The oldest searches are dropped once the history grows too long.
+pub fn append_history(search: &str) -> Result<(), Box<dyn Error>> {
+    let search = search.trim();
+
+    if search.is_empty() {
+        return Ok(());
+    }
+
+    let path = history_path();
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)?;
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(&path)?;
+
+    if last_entry(&mut file)?.is_some_and(|last| last == search) {
+        return Ok(());
+    }
+    writeln!(file, "{}", search)?;
+
+    if file.metadata()?.len() > TRIM_HISTORY_BYTES {
+        let history = read_history()?;
+        let kept = &history[history.len().saturating_sub(MAX_HISTORY)..];
+        fs::write(&path, kept.join("\n") + "\n")?;
+    }
+
+    Ok(())
+}
+
+/// Reads the search history, oldest search first.
+pub fn read_history() -> Result<Vec<String>, Box<dyn Error>> {
+    let Ok(file) = File::open(history_path()) else {
+        return Ok(vec![]);
+    };
+
+    let reader = BufReader::new(file);
+    let mut result = Vec::<String>::new();
+
+    for line in reader.lines().map_while(Result::ok) {
+        result.push(line);
+    }
+
+    Ok(result)
+}
+
+/// Recalls searches from the history while typing a search, either by stepping
+/// through it one search at a time or by a reverse search for a pattern.
+pub struct HistoryRecall {
+    entries: Vec<String>,
+    index: Option<usize>,
+    draft: String,
+    reverse_search: Option<String>,
+    reverse_match: Option<usize>,
+}
+
+impl HistoryRecall {
+    pub fn new(entries: Vec<String>) -> HistoryRecall {
+        HistoryRecall {
+            entries,
+            index: None,
+            draft: String::new(),
+            reverse_search: None,
+            reverse_match: None,
+        }
+    }
+
+    /// Adds a search to the recallable entries and starts over from the newest search.
+    pub fn push(&mut self, search: &str) {
+        if !search.is_empty() && self.entries.last().map(String::as_str) != Some(search) {
+            self.entries.push(search.to_string());
+        }
+
+        self.index = None;
+        self.reverse_search = None;
+        self.reverse_match = None;
+    }
+
+    /// Steps back to the previous search. The current search is kept as a draft,
+    /// so it can be returned to by stepping forward again.
+    pub fn previous(&mut self, current: &str) -> Option<String> {
+        let index = match self.index {
+            None if self.entries.is_empty() => return None,
+            None => {
+                self.draft = current.to_string();
+                self.entries.len() - 1
+            }
+            Some(0) => return None,
+            Some(index) => index - 1,
+        };
+
+        self.index = Some(index);
+        Some(self.entries[index].clone())
+    }
+
+    /// Steps forward to the next search, or back to the draft after the newest search.
+    pub fn next(&mut self) -> Option<String> {
+        let index = self.index?;
+
+        if index + 1 < self.entries.len() {
+            self.index = Some(index + 1);
+            Some(self.entries[index + 1].clone())
+        } else {
+            self.index = None;
+            Some(self.draft.clone())
+        }
+    }
+
+    pub fn is_reverse_searching(&self) -> bool {
+        self.reverse_search.is_some()
+    }
+
+    /// Starts a reverse search, or moves on to an older match if already searching.
+    pub fn reverse_search(&mut self) {
+        match &self.reverse_search {
+            None => {
+                self.reverse_search = Some(String::new());
+                self.reverse_match = None;
+            }
+            Some(_) => {
+                let before = self.reverse_match.unwrap_or(self.entries.len());
+                if let Some(older) = self.find_match(before) {
+                    self.reverse_match = Some(older);
+                }
+            }
+        }
+    }
+
+    pub fn reverse_search_push(&mut self, c: char) {
+        if let Some(pattern) = &mut self.reverse_search {
+            pattern.push(c);
+            self.reverse_match = self.find_match(self.entries.len());
+        }
+    }
+
+    pub fn reverse_search_pop(&mut self) {
+        if let Some(pattern) = &mut self.reverse_search {
+            pattern.pop();
+            self.reverse_match = self.find_match(self.entries.len());
+        }
+    }
+
+    /// Finds the newest search containing the pattern, older than the given index.
+    fn find_match(&self, before: usize) -> Option<usize> {
+        let pattern = self.reverse_search.as_ref()?;
+
+        if pattern.is_empty() {
+            return None;
+        }
+
+        self.entries[..before]
+            .iter()
+            .rposition(|entry| entry.contains(pattern.as_str()))
+    }
+
+    pub fn reverse_search_pattern(&self) -> &str {
+        self.reverse_search.as_deref().unwrap_or_default()
+    }
+
+    pub fn reverse_search_match(&self) -> Option<&str> {
+        self.reverse_match.map(|i| self.entries[i].as_str())
+    }
+
+    /// Ends the reverse search, returning the matched search if there was one.
+    pub fn end_reverse_search(&mut self) -> Option<String> {
+        let result = self.reverse_search_match().map(String::from);
+
+        self.reverse_search = None;
+        self.reverse_match = None;
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recall() -> HistoryRecall {
+        HistoryRecall::new(vec!["home".to_string(), "arrow".to_string()])
+    }
+
+    #[test]
+    fn steps_back_to_the_oldest_search() {
+        let mut history = recall();
+
+        assert_eq!(history.previous("draft").as_deref(), Some("arrow"));
+        assert_eq!(history.previous("arrow").as_deref(), Some("home"));
+        // There is nothing older, so the oldest search stays.
+        assert_eq!(history.previous("home"), None);
+        assert_eq!(history.next().as_deref(), Some("arrow"));
+    }
+
+    #[test]
+    fn steps_forward_to_the_draft() {
+        let mut history = recall();
+
+        history.previous("draft");
+        history.previous("arrow");
+        assert_eq!(history.next().as_deref(), Some("arrow"));
+        assert_eq!(history.next().as_deref(), Some("draft"));
+        // Past the draft, there is nothing newer.
+        assert_eq!(history.next(), None);
+    }
+
+    #[test]
+    fn keeps_the_latest_draft() {
+        let mut history = recall();
+
+        history.previous("first");
+        history.next();
+        history.previous("second");
+        assert_eq!(history.next().as_deref(), Some("second"));
+    }
+
+    #[test]
+    fn empty_history() {
+        let mut history = HistoryRecall::new(vec![]);
+
+        assert_eq!(history.previous("draft"), None);
+        assert_eq!(history.next(), None);
+    }
+
+    #[test]
+    fn push_starts_over_from_the_newest_search() {
+        let mut history = recall();
+
+        history.previous("");
+        history.previous("");
+        history.push("star");
+        history.push("star");
+
+        assert_eq!(history.previous("").as_deref(), Some("star"));
+        assert_eq!(history.previous("").as_deref(), Some("arrow"));
+    }
+
+    #[test]
+    fn reverse_search_finds_older_matches() {
+        let mut history = HistoryRecall::new(vec![
+            "arrow-left".to_string(),
+            "home".to_string(),
+            "arrow-right".to_string(),
+        ]);
+
+        history.reverse_search();
+        history.reverse_search_push('a');
+        history.reverse_search_push('r');
+        assert_eq!(history.reverse_search_match(), Some("arrow-right"));
+
+        history.reverse_search();
+        assert_eq!(history.reverse_search_match(), Some("arrow-left"));
+
+        // Without older matches, the match stays.
+        history.reverse_search();
+        assert_eq!(history.end_reverse_search().as_deref(), Some("arrow-left"));
+        assert!(!history.is_reverse_searching());
+    }
+}
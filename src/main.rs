@@ -1,19 +1,47 @@
-use std::{collections::HashMap, error::Error};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs,
+    io::{stdout, IsTerminal},
+};
 
 use clap::Parser;
-use config::read_config_file;
+use config::{get_search_api, read_config_file, Config};
+pub use icon_core::enums;
 use log::LevelFilter;
 use resvg::usvg::fontdb;
 use simplelog::{ColorChoice, CombinedLogger, ConfigBuilder, TermLogger, TerminalMode};
 
-use crate::{cli::Cli, files::preview};
+use crate::{
+    cli::{CacheCommand, Cli, Command, ConfigCommand, OutputFormat, SearchArgs},
+    files::preview,
+    output::Highlighter,
+};
 
+mod app_icons;
 mod browse;
 mod cli;
+mod collections;
+mod components;
 pub mod config;
-pub mod enums;
+mod css;
+mod export;
+mod favicon;
 pub mod files;
+mod font;
+mod history;
+mod output;
 mod parser;
+mod render;
+mod rust_components;
+mod search;
+mod sfnt;
+mod show;
+mod similar;
+mod sprite;
+mod svg;
+mod template;
+mod traits;
 
 fn main() -> Result<(), Box<dyn Error>> {
     let args = Cli::parse();
@@ -36,41 +64,174 @@ fn main() -> Result<(), Box<dyn Error>> {
         ColorChoice::Auto,
     )])?;
 
-    if args.reset_config {
-        config::create_default_config_file()?;
+    // The config commands don't read the config, so a broken one can be reset.
+    if let Some(Command::Config(config_command)) = &args.command {
+        match config_command {
+            ConfigCommand::Show => print!("{}", fs::read_to_string(config::config_path())?),
+            ConfigCommand::Reset => {
+                config::create_default_config_file()?;
+            }
+            ConfigCommand::Path => println!("{}", config::config_path().display()),
+        }
+
+        return Ok(());
     }
 
     let config = read_config_file()?;
 
-    let browse = if let Some(default_browse) = config.default_browse {
-        (args.browse || default_browse) && !args.no_browse
-    } else {
-        args.browse
-    };
-
-    if args.fetch_collections {
-        files::fetch_collections(true)?;
+    match &args.command {
+        Some(Command::Search(search_args)) => {
+            search(search_args, &config, &mut collections_cache, &mut fontdb)?
+        }
+        Some(Command::Browse(browse_args)) => {
+            if browse_args.query.query.is_some() || browse_args.query.prefix.is_some() {
+                history::append_history(&browse::parse_original_search_string(
+                    &browse_args.query,
+                )?)?;
+            }
+            browse::browse(browse_args, &config, &mut collections_cache, &mut fontdb)?;
+        }
+        Some(Command::Show(show_args)) => {
+            show::show(show_args, &mut collections_cache, &mut fontdb)?
+        }
+        Some(Command::Export(export_args)) => export::export(export_args, &mut collections_cache)?,
+        Some(Command::Raster(raster_args)) => {
+            export::raster(raster_args, &mut collections_cache, &fontdb)?
+        }
+        Some(Command::Favicon(favicon_args)) => {
+            favicon::favicon(favicon_args, &mut collections_cache, &fontdb)?
+        }
+        Some(Command::AppIcons(app_icons_args)) => {
+            app_icons::app_icons(app_icons_args, &mut collections_cache, &fontdb)?
+        }
+        Some(Command::Sprite(sprite_args)) => sprite::sprite(sprite_args, &mut collections_cache)?,
+        Some(Command::Components(components_args)) => {
+            components::components(components_args, &mut collections_cache)?
+        }
+        Some(Command::Font(font_args)) => font::font(font_args, &mut collections_cache, &fontdb)?,
+        Some(Command::Css(css_args)) => css::css(css_args, &mut collections_cache)?,
+        Some(Command::Collections(collections_args)) => {
+            collections::collections(collections_args, &mut collections_cache, &fontdb)?
+        }
+        Some(Command::Cache(cache_command)) => match cache_command {
+            CacheCommand::Fetch => {
+                files::fetch_collections(true)?;
+            }
+            CacheCommand::Generate => {
+                files::generate_cached_icons()?;
+            }
+            CacheCommand::Path => println!("{}", config::cache_dir().display()),
+        },
+        Some(Command::Config(_)) => unreachable!("config commands don't read the config"),
+        None => {
+            let browse = if let Some(default_browse) = config.default_browse {
+                (args.browse || default_browse) && !args.no_browse
+            } else {
+                args.browse
+            };
+
+            if browse {
+                let browse_args = args.browse_args();
+                if browse_args.query.query.is_some() || browse_args.query.prefix.is_some() {
+                    history::append_history(&browse::parse_original_search_string(
+                        &browse_args.query,
+                    )?)?;
+                }
+                browse::browse(&browse_args, &config, &mut collections_cache, &mut fontdb)?;
+            } else {
+                search(&args.search, &config, &mut collections_cache, &mut fontdb)?;
+            }
+        }
     }
 
-    if args.generate_icons_cache {
-        files::generate_cached_icons()?;
+    Ok(())
+}
+
+/// Prints the icons matching a search, or the icons most similar to another one.
+fn search(
+    args: &SearchArgs,
+    config: &Config,
+    collections_cache: &mut HashMap<String, enums::IconCollection>,
+    fontdb: &mut fontdb::Database,
+) -> Result<(), Box<dyn Error>> {
+    if args.history {
+        for search in history::read_history()? {
+            println!("{}", search);
+        }
     }
 
-    if browse {
-        browse::browse(&args, &mut collections_cache, &mut fontdb)?;
+    if let Some(target) = &args.similar {
+        let limit = args.limit.unwrap_or(similar::SIMILAR_RESULTS);
+        let results =
+            similar::find_similar(target, args.offset + limit, collections_cache, fontdb)?;
+
+        let shown = search::page(&results, args.offset, Some(limit));
+
+        if args.format == OutputFormat::Plain {
+            for r in shown {
+                if args.preview {
+                    preview(r, collections_cache, None, fontdb)?;
+                }
+                println!("{}", r);
+                if args.preview {
+                    println!();
+                }
+            }
+        } else {
+            output::print_records(shown, args.format, args.with_body, collections_cache, None)?;
+        }
     }
 
-    if (args.query.is_some() || args.prefix.is_some()) && !browse {
-        let results = files::query(&args.query, &args.prefix)?;
+    let query = &args.query;
+
+    if query.query.is_some() || query.prefix.is_some() {
+        history::append_history(&browse::parse_original_search_string(query)?)?;
 
-        for r in &results {
-            if args.preview {
-                preview(r, &mut HashMap::new(), &mut fontdb)?;
+        let api = get_search_api(query, config);
+        let results = search::search(&query.query, &query.prefix, &query.theme, &api, config)?;
+
+        if args.count {
+            println!("{}", results.len());
+            return Ok(());
+        }
+
+        let shown = search::page(&results, args.offset, args.limit);
+
+        // Icons are fetched from the search API in one go rather than one by one.
+        let api = api.as_deref();
+        if let Some(api) = api {
+            if args.preview || args.format != OutputFormat::Plain {
+                files::fetch_api_icons(api, shown, collections_cache)?;
             }
-            println!("{}", r);
-            if args.preview {
-                println!("");
+        }
+
+        if args.format == OutputFormat::Plain {
+            let highlighter = Highlighter::new(&query.query, config)?;
+            let highlight = stdout().is_terminal();
+
+            for r in shown {
+                if args.preview {
+                    preview(r, collections_cache, api, fontdb)?;
+                }
+                if highlight {
+                    println!("{}", highlighter.highlight(r));
+                } else {
+                    println!("{}", r);
+                }
+                if args.preview {
+                    println!();
+                }
             }
+        } else {
+            output::print_records(shown, args.format, args.with_body, collections_cache, api)?;
+        }
+
+        // The footer goes to stderr, so the output can still be piped.
+        if shown.len() < results.len() {
+            eprintln!(
+                "{}",
+                search::page_summary(args.offset, shown.len(), results.len())
+            );
         }
     }
 
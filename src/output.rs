@@ -0,0 +1,279 @@
+use std::{collections::HashMap, error::Error};
+
+use crossterm::style::{Color, Stylize};
+use serde::Serialize;
+
+use crate::{
+    cli::OutputFormat,
+    config::Config,
+    enums::{Author, IconCollection, License},
+    files::{get_cached_traits, get_icon_xml},
+    parser::{self, Span, Symbol},
+    search::expand_saved_searches,
+    traits::IconTraits,
+};
+
+/// The colour of the parts of an icon identifier matched by the query.
+pub const HIGHLIGHT_COLOR: Color = Color::Yellow;
+
+/// Finds the parts of icon identifiers that were matched by a query.
+pub struct Highlighter {
+    parsed_query: Option<Symbol>,
+    traits: HashMap<String, IconTraits>,
+}
+
+impl Highlighter {
+    pub fn new(query: &Option<String>, config: &Config) -> Result<Highlighter, Box<dyn Error>> {
+        let (query, _) = expand_saved_searches(query, &None, config)?;
+
+        let parsed_query = match query {
+            Some(query) if !query.trim().is_empty() => Some(parser::parse(&query)?),
+            _ => None,
+        };
+
+        // Filters can decide which branch of the query matched, so their traits are needed too.
+        let traits = match &parsed_query {
+            Some(parsed_query) if parser::has_filters(parsed_query) => {
+                get_cached_traits().unwrap_or_default()
+            }
+            _ => HashMap::new(),
+        };
+
+        Ok(Highlighter {
+            parsed_query,
+            traits,
+        })
+    }
+
+    fn spans(&self, icon: &str) -> Vec<Span> {
+        let Some(parsed_query) = &self.parsed_query else {
+            return vec![];
+        };
+
+        let no_traits = IconTraits::default();
+        let traits = self.traits.get(icon).unwrap_or(&no_traits);
+
+        parser::match_query(icon, traits, parsed_query)
+            .ok()
+            .flatten()
+            .unwrap_or_default()
+    }
+
+    /// Splits the icon identifier into segments, flagged if they were matched by the query.
+    /// Overlapping and adjacent matches are merged into a single segment.
+    pub fn segments<'a>(&self, icon: &'a str) -> Vec<(&'a str, bool)> {
+        let mut matched = vec![false; icon.len()];
+        for (start, end) in self.spans(icon) {
+            matched[start..end].fill(true);
+        }
+
+        let mut segments = Vec::new();
+        let mut segment_start = 0;
+
+        for i in 1..=icon.len() {
+            if i == icon.len() || matched[i] != matched[segment_start] {
+                segments.push((&icon[segment_start..i], matched[segment_start]));
+                segment_start = i;
+            }
+        }
+
+        segments
+    }
+
+    /// Returns the icon identifier with the matched parts coloured for the terminal.
+    pub fn highlight(&self, icon: &str) -> String {
+        self.segments(icon)
+            .into_iter()
+            .map(|(segment, matched)| {
+                if matched {
+                    segment.with(HIGHLIGHT_COLOR).bold().to_string()
+                } else {
+                    segment.to_string()
+                }
+            })
+            .collect()
+    }
+}
+
+/// The details of an icon, as printed by the json, ndjson and tsv formats.
+#[derive(Debug, Serialize)]
+pub struct IconRecord {
+    pub prefix: String,
+    pub name: String,
+    pub collection: String,
+    pub license: License,
+    pub author: Author,
+    pub width: usize,
+    pub height: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+}
+
+impl IconRecord {
+    pub fn new(
+        icon: &str,
+        with_body: bool,
+        collections_cache: &mut HashMap<String, IconCollection>,
+        api: Option<&str>,
+    ) -> Result<IconRecord, Box<dyn Error>> {
+        let (width, height, body) = get_icon_xml(icon, collections_cache, api)?;
+        let (prefix, name) = icon.split_once(':').ok_or("Invalid icon identifier.")?;
+
+        // Looking up the icon has put its collection in the cache.
+        let info = &collections_cache[prefix].info;
+
+        Ok(IconRecord {
+            prefix: prefix.to_string(),
+            name: name.to_string(),
+            collection: info.name.clone(),
+            license: info.license.clone(),
+            author: info.author.clone(),
+            width,
+            height,
+            body: Some(body).filter(|_| with_body),
+        })
+    }
+
+    const TSV_HEADER: [&'static str; 9] = [
+        "prefix",
+        "name",
+        "collection",
+        "license",
+        "license_url",
+        "author",
+        "width",
+        "height",
+        "body",
+    ];
+
+    /// The header of the tsv format, which only has a body column with `--with-body`.
+    fn tsv_header(with_body: bool) -> String {
+        let columns = if with_body { 9 } else { 8 };
+        IconRecord::TSV_HEADER[..columns].join("\t")
+    }
+
+    fn tsv_row(&self) -> String {
+        let mut fields = vec![
+            self.prefix.clone(),
+            self.name.clone(),
+            self.collection.clone(),
+            self.license.spdx.clone(),
+            self.license.url.clone().unwrap_or_default(),
+            self.author.name.clone(),
+            self.width.to_string(),
+            self.height.to_string(),
+        ];
+        if let Some(body) = &self.body {
+            fields.push(body.clone());
+        }
+
+        // Tabs and line breaks would break up the columns and rows.
+        fields
+            .iter()
+            .map(|field| field.replace(['\t', '\n', '\r'], " "))
+            .collect::<Vec<String>>()
+            .join("\t")
+    }
+}
+
+/// Prints the icons in one of the machine readable formats.
+pub fn print_records(
+    icons: &[String],
+    format: OutputFormat,
+    with_body: bool,
+    collections_cache: &mut HashMap<String, IconCollection>,
+    api: Option<&str>,
+) -> Result<(), Box<dyn Error>> {
+    let records = icons
+        .iter()
+        .map(|icon| IconRecord::new(icon, with_body, collections_cache, api))
+        .collect::<Result<Vec<IconRecord>, Box<dyn Error>>>()?;
+
+    match format {
+        OutputFormat::Plain => {
+            for record in records {
+                println!("{}:{}", record.prefix, record.name);
+            }
+        }
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&records)?),
+        OutputFormat::Ndjson => {
+            for record in records {
+                println!("{}", serde_json::to_string(&record)?);
+            }
+        }
+        OutputFormat::Tsv => {
+            println!("{}", IconRecord::tsv_header(with_body));
+
+            for record in records {
+                println!("{}", record.tsv_row());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(with_body: bool) -> IconRecord {
+        let collection: IconCollection = serde_json::from_value(serde_json::json!({
+            "prefix": "mdi",
+            "width": 24,
+            "height": 24,
+            "lastModified": 0,
+            "info": {
+                "name": "Material\tDesign Icons",
+                "total": 1,
+                "author": { "name": "Pictogrammers" },
+                "license": {
+                    "title": "Apache 2.0",
+                    "spdx": "Apache-2.0",
+                    "url": "https://www.apache.org/licenses/LICENSE-2.0"
+                },
+                "samples": [],
+                "palette": false
+            },
+            "icons": { "arrow-left": { "body": "<path\td=\"M20 11v2H8\"/>\r\n<path/>" } }
+        }))
+        .unwrap();
+        let mut collections_cache = HashMap::from([("mdi".to_string(), collection)]);
+
+        IconRecord::new("mdi:arrow-left", with_body, &mut collections_cache, None).unwrap()
+    }
+
+    #[test]
+    fn tsv_rows_have_a_column_per_header() {
+        for with_body in [false, true] {
+            let header = IconRecord::tsv_header(with_body);
+            let row = record(with_body).tsv_row();
+            let columns = if with_body { 9 } else { 8 };
+
+            assert_eq!(header.split('\t').count(), columns);
+            assert_eq!(row.split('\t').count(), columns);
+        }
+
+        assert!(!IconRecord::tsv_header(false).contains("body"));
+        assert!(IconRecord::tsv_header(true).ends_with("\tbody"));
+    }
+
+    #[test]
+    fn tsv_fields_are_escaped() {
+        assert_eq!(
+            record(true).tsv_row(),
+            [
+                "mdi",
+                "arrow-left",
+                "Material Design Icons",
+                "Apache-2.0",
+                "https://www.apache.org/licenses/LICENSE-2.0",
+                "Pictogrammers",
+                "24",
+                "24",
+                "<path d=\"M20 11v2H8\"/>  <path/>",
+            ]
+            .join("\t")
+        );
+    }
+}
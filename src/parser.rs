@@ -1,8 +1,11 @@
 use std::error::Error;
 
+use crate::traits::{filter_values, IconTraits, FILTER_KEYS};
+
 #[derive(Debug, Clone)]
 pub enum Symbol {
     Phrase(String),
+    Filter(String, String),
     Group(Vec<Symbol>),
     And(Box<Symbol>, Box<Symbol>),
     Or(Box<Symbol>, Box<Symbol>),
@@ -32,7 +35,7 @@ fn parse_expression<'a>(tokens: &'a [&'a str]) -> (Symbol, &'a [&'a str]) {
 }
 
 fn parse_primary<'a>(tokens: &'a [&'a str]) -> (Symbol, &'a [&'a str]) {
-    match tokens.get(0) {
+    match tokens.first() {
         Some(&"(") => {
             let (expr, rest) = parse_expression(&tokens[1..]);
             if rest[0] != ")" {
@@ -44,30 +47,36 @@ fn parse_primary<'a>(tokens: &'a [&'a str]) -> (Symbol, &'a [&'a str]) {
             let (expr, rest) = parse_primary(&tokens[1..]);
             (Symbol::Not(Box::new(expr)), rest)
         }
-        Some(phrase) => (Symbol::Phrase(phrase.to_string()), &tokens[1..]),
+        Some(phrase) => match phrase.split_once(':') {
+            Some((key, value)) if is_filter_key(key) => (
+                Symbol::Filter(key.to_string(), value.to_string()),
+                &tokens[1..],
+            ),
+            _ => (Symbol::Phrase(phrase.to_string()), &tokens[1..]),
+        },
         None => panic!("Unexpected end of input"),
     }
 }
 
-fn parse_tokens<'a>(to_parse: &'a str) -> Result<Vec<&'a str>, Box<dyn Error>> {
+fn parse_tokens(to_parse: &str) -> Result<Vec<&str>, Box<dyn Error>> {
     let delimiters = ['!', '&', '|', '(', ')', ' '];
     let mut tokens = Vec::new();
     let mut start = 0;
     let mut in_token = false;
 
-    for (i, c) in to_parse.chars().enumerate() {
+    for (i, c) in to_parse.char_indices() {
         if delimiters.contains(&c) {
             if in_token {
                 tokens.push(&to_parse[start..i]);
                 in_token = false;
             }
-            tokens.push(&to_parse[i..i + 1]);
+            tokens.push(&to_parse[i..i + c.len_utf8()]);
         } else if !c.is_whitespace() {
             if !in_token {
                 start = i;
                 in_token = true;
             }
-            if i == to_parse.len() - 1 {
+            if i + c.len_utf8() == to_parse.len() {
                 tokens.push(&to_parse[start..]);
             }
         } else {
@@ -81,32 +90,138 @@ fn parse_tokens<'a>(to_parse: &'a str) -> Result<Vec<&'a str>, Box<dyn Error>> {
     Ok(tokens)
 }
 
-pub fn match_query(icon_string: String, parsed_query: Symbol) -> Result<bool, Box<dyn Error>> {
+pub fn is_filter_key(key: &str) -> bool {
+    FILTER_KEYS.contains(&key)
+}
+
+/// Checks if the query contains filters, which need the traits of the icons to be matched.
+pub fn has_filters(symbol: &Symbol) -> bool {
+    match symbol {
+        Symbol::Phrase(_) => false,
+        Symbol::Filter(_, _) => true,
+        Symbol::Group(group) => group.iter().any(has_filters),
+        Symbol::And(left, right) | Symbol::Or(left, right) => {
+            has_filters(left) || has_filters(right)
+        }
+        Symbol::Not(expr) => has_filters(expr),
+    }
+}
+
+/// Checks that the filters in the query have values they accept, as an unknown
+/// value would silently match nothing.
+fn validate_filters(symbol: &Symbol) -> Result<(), Box<dyn Error>> {
+    match symbol {
+        Symbol::Phrase(_) => Ok(()),
+        Symbol::Filter(key, value) => {
+            let values = filter_values(key);
+            if values.contains(&value.as_str()) {
+                Ok(())
+            } else {
+                Err(format!(
+                    "Unknown value '{}' for the {} filter, expected one of: {}.",
+                    value,
+                    key,
+                    values.join(", ")
+                )
+                .into())
+            }
+        }
+        Symbol::Group(group) => group.iter().try_for_each(validate_filters),
+        Symbol::And(left, right) | Symbol::Or(left, right) => {
+            validate_filters(left)?;
+            validate_filters(right)
+        }
+        Symbol::Not(expr) => validate_filters(expr),
+    }
+}
+
+/// A matched range of bytes in the icon string.
+pub type Span = (usize, usize);
+
+/// Matches the icon against the query. Returns `None` if the icon doesn't
+/// match, otherwise the spans of the phrases that made it match.
+pub fn match_query(
+    icon_string: &str,
+    traits: &IconTraits,
+    parsed_query: &Symbol,
+) -> Result<Option<Vec<Span>>, Box<dyn Error>> {
     match parsed_query {
-        Symbol::Phrase(phrase) => Ok(icon_string.contains(&phrase)),
+        Symbol::Phrase(phrase) => {
+            let spans: Vec<Span> = icon_string
+                .match_indices(phrase.as_str())
+                .map(|(i, m)| (i, i + m.len()))
+                .collect();
+
+            Ok(Some(spans).filter(|spans| !spans.is_empty()))
+        }
+        Symbol::Filter(key, value) => Ok(Some(vec![]).filter(|_| traits.matches(key, value))),
         Symbol::Group(group) => {
+            let mut spans = Vec::new();
             for symbol in group {
-                if !match_query(icon_string.clone(), symbol)? {
-                    return Ok(false);
+                match match_query(icon_string, traits, symbol)? {
+                    Some(symbol_spans) => spans.extend(symbol_spans),
+                    None => return Ok(None),
                 }
             }
-            Ok(true)
+            Ok(Some(spans))
         }
         Symbol::And(left, right) => {
-            Ok(match_query(icon_string.clone(), *left)?
-                && match_query(icon_string.clone(), *right)?)
+            let Some(mut spans) = match_query(icon_string, traits, left)? else {
+                return Ok(None);
+            };
+            let Some(right_spans) = match_query(icon_string, traits, right)? else {
+                return Ok(None);
+            };
+            spans.extend(right_spans);
+            Ok(Some(spans))
         }
         Symbol::Or(left, right) => {
-            Ok(match_query(icon_string.clone(), *left)?
-                || match_query(icon_string.clone(), *right)?)
+            match (
+                match_query(icon_string, traits, left)?,
+                match_query(icon_string, traits, right)?,
+            ) {
+                (Some(mut spans), Some(right_spans)) => {
+                    spans.extend(right_spans);
+                    Ok(Some(spans))
+                }
+                (Some(spans), None) | (None, Some(spans)) => Ok(Some(spans)),
+                (None, None) => Ok(None),
+            }
         }
-        Symbol::Not(expr) => Ok(!match_query(icon_string, *expr)?),
+        Symbol::Not(expr) => match match_query(icon_string, traits, expr)? {
+            Some(_) => Ok(None),
+            None => Ok(Some(vec![])),
+        },
     }
 }
 
 pub fn parse(to_parse: &str) -> Result<Symbol, Box<dyn Error>> {
     let tokens = parse_tokens(to_parse)?;
     let (parsed, _) = parse_expression(&tokens);
+    validate_filters(&parsed)?;
 
     Ok(parsed)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_known_filter_values() {
+        for query in ["style:stroke", "arrow & color:multi", "!animated:true"] {
+            assert!(parse(query).is_ok(), "{}", query);
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_filter_values() {
+        let error = parse("arrow & style:foo").unwrap_err().to_string();
+        assert_eq!(
+            error,
+            "Unknown value 'foo' for the style filter, expected one of: stroke, fill."
+        );
+
+        assert!(parse("!(color:rainbow)").is_err());
+    }
+}
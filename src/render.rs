@@ -0,0 +1,138 @@
+use std::{error::Error, path::Path};
+
+use image::{ImageFormat, RgbaImage};
+use resvg::{tiny_skia::Pixmap, usvg::fontdb::Database};
+
+use crate::{
+    files::render_svg,
+    svg::{style_body, SvgOptions},
+};
+
+/// The space around an icon, either in pixels at 1x or as a percentage of the size.
+#[derive(Clone, Copy, Debug)]
+pub enum Padding {
+    Pixels(f32),
+    Percent(f32),
+}
+
+impl Padding {
+    /// The padding in pixels, for an image of the given size at 1x and density.
+    pub fn to_pixels(self, size: f32, scale: f32) -> f32 {
+        match self {
+            Padding::Pixels(pixels) => pixels * scale,
+            Padding::Percent(percent) => size * scale * percent / 100.0,
+        }
+    }
+}
+
+impl std::str::FromStr for Padding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |_| format!("Invalid padding '{}', expected e.g. 2 or 10%.", s);
+
+        match s.strip_suffix('%') {
+            Some(percent) => Ok(Padding::Percent(percent.trim().parse().map_err(invalid)?)),
+            None => Ok(Padding::Pixels(s.trim().parse().map_err(invalid)?)),
+        }
+    }
+}
+
+/// How an icon is drawn on a square image.
+#[derive(Clone, Debug)]
+pub struct RasterOptions {
+    /// The colour of monochrome icons.
+    pub color: String,
+    /// The space between the icon and the edges of the image.
+    pub padding: Padding,
+    /// The colour the image is filled with, or transparent if not set.
+    pub background: Option<String>,
+    /// The radius of the corners of the background, as a percentage of the size.
+    pub corner_radius: f32,
+}
+
+impl Default for RasterOptions {
+    fn default() -> Self {
+        RasterOptions {
+            color: "#000".to_string(),
+            padding: Padding::Pixels(0.0),
+            background: None,
+            corner_radius: 0.0,
+        }
+    }
+}
+
+/// Builds a square SVG document of `pixels` by `pixels` with the icon centered on it.
+pub fn icon_on_canvas(
+    width: usize,
+    height: usize,
+    body: &str,
+    pixels: u32,
+    padding: f32,
+    options: &RasterOptions,
+) -> String {
+    let background = match &options.background {
+        Some(background) => {
+            let radius = pixels as f32 * options.corner_radius / 100.0;
+            format!(
+                r#"<rect width="{}" height="{}" rx="{}" fill="{}"/>"#,
+                pixels, pixels, radius, background
+            )
+        }
+        None => String::new(),
+    };
+
+    let inner = (pixels as f32 - 2.0 * padding).max(1.0);
+    let body = style_body(
+        body,
+        &SvgOptions {
+            color: options.color.clone(),
+            ..Default::default()
+        },
+    );
+
+    // The nested document fits the icon inside the padding, keeping its aspect ratio.
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{0}" viewBox="0 0 {0} {0}">{1}<svg x="{2}" y="{2}" width="{3}" height="{3}" viewBox="0 0 {4} {5}">{6}</svg></svg>"#,
+        pixels, background, padding, inner, width, height, body
+    )
+}
+
+/// Renders an icon centered on a square image of `pixels` by `pixels`.
+pub fn render_icon(
+    width: usize,
+    height: usize,
+    body: &str,
+    pixels: u32,
+    padding: f32,
+    options: &RasterOptions,
+    fontdb: &Database,
+) -> Result<Pixmap, Box<dyn Error>> {
+    let svg = icon_on_canvas(width, height, body, pixels, padding, options);
+
+    render_svg(svg.as_bytes(), fontdb)
+}
+
+/// Converts a rendered pixmap, which has premultiplied alpha, to an image.
+pub fn pixmap_to_image(pixmap: &Pixmap) -> RgbaImage {
+    let mut data = Vec::with_capacity(pixmap.data().len());
+
+    for pixel in pixmap.pixels() {
+        let color = pixel.demultiply();
+        data.extend_from_slice(&[color.red(), color.green(), color.blue(), color.alpha()]);
+    }
+
+    RgbaImage::from_raw(pixmap.width(), pixmap.height(), data)
+        .expect("pixmap has four bytes per pixel")
+}
+
+/// Saves a rendered pixmap as an image file of the given format.
+pub fn save_pixmap(
+    pixmap: &Pixmap,
+    path: &Path,
+    format: ImageFormat,
+) -> Result<(), Box<dyn Error>> {
+    pixmap_to_image(pixmap).save_with_format(path, format)?;
+
+    Ok(())
+}
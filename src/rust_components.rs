@@ -0,0 +1,271 @@
+use std::{collections::HashMap, error::Error};
+
+use crate::{
+    cli::{ComponentsArgs, Framework},
+    components::{check_unique_names, component_name},
+    enums::IconCollection,
+    files::{get_icon_xml, write_file},
+    svg::{standalone_svg, SvgOptions},
+};
+
+const HEADER: &str = "// Generated by icon. Do not edit.\n";
+
+const ICON_IMPL: &str = r#"
+impl Icon {
+    /// The body of the icon, preceded by a title for assistive technologies.
+    pub fn inner_html(self, title: Option<&str>) -> String {
+        match title {
+            Some(title) => format!(
+                "<title>{}</title>{}",
+                title.replace('&', "&amp;").replace('<', "&lt;"),
+                self.body()
+            ),
+            None => self.body().to_string(),
+        }
+    }
+}
+"#;
+
+const LEPTOS_COMPONENT: &str = r#"
+#[component]
+pub fn SvgIcon(
+    icon: Icon,
+    #[prop(into, default = "1em".to_string())] size: String,
+    #[prop(into, default = "currentColor".to_string())] color: String,
+    #[prop(into, optional)] title: Option<String>,
+) -> impl IntoView {
+    let role = title.is_some().then_some("img");
+    let aria_hidden = title.is_none().then_some("true");
+
+    view! {
+        <svg
+            xmlns="http://www.w3.org/2000/svg"
+            viewBox=icon.view_box()
+            width=size.clone()
+            height=size
+            color=color
+            role=role
+            aria-hidden=aria_hidden
+            inner_html=icon.inner_html(title.as_deref())
+        ></svg>
+    }
+}
+"#;
+
+const YEW_COMPONENT: &str = r#"
+#[derive(Properties, PartialEq)]
+pub struct SvgIconProps {
+    pub icon: Icon,
+    #[prop_or(AttrValue::Static("1em"))]
+    pub size: AttrValue,
+    #[prop_or(AttrValue::Static("currentColor"))]
+    pub color: AttrValue,
+    #[prop_or_default]
+    pub title: Option<AttrValue>,
+}
+
+#[function_component]
+pub fn SvgIcon(props: &SvgIconProps) -> Html {
+    let inner = Html::from_html_unchecked(props.icon.inner_html(props.title.as_deref()).into());
+
+    html! {
+        <svg
+            xmlns="http://www.w3.org/2000/svg"
+            viewBox={props.icon.view_box()}
+            width={props.size.clone()}
+            height={props.size.clone()}
+            color={props.color.clone()}
+            role={props.title.as_ref().map(|_| "img")}
+            aria-hidden={props.title.is_none().then_some("true")}
+        >
+            {inner}
+        </svg>
+    }
+}
+"#;
+
+const DIOXUS_COMPONENT: &str = r#"
+#[component]
+pub fn SvgIcon(
+    icon: Icon,
+    #[props(into, default = "1em".to_string())] size: String,
+    #[props(into, default = "currentColor".to_string())] color: String,
+    #[props(into)] title: Option<String>,
+) -> Element {
+    rsx! {
+        svg {
+            xmlns: "http://www.w3.org/2000/svg",
+            view_box: icon.view_box(),
+            width: "{size}",
+            height: "{size}",
+            color: "{color}",
+            role: title.as_ref().map(|_| "img"),
+            "aria-hidden": title.is_none().then_some("true"),
+            dangerous_inner_html: icon.inner_html(title.as_deref()),
+        }
+    }
+}
+"#;
+
+/// The icons of a generated module, with their data.
+struct GeneratedIcon {
+    id: String,
+    variant: String,
+    width: usize,
+    height: usize,
+    body: String,
+}
+
+/// Generates a Rust module with an `Icon` enum of the selected icons, and either
+/// an `SvgIcon` component for a web framework or image sources for egui.
+pub fn rust_module(
+    args: &ComponentsArgs,
+    icons: &[String],
+    collections_cache: &mut HashMap<String, IconCollection>,
+) -> Result<(), Box<dyn Error>> {
+    check_unique_names(icons, component_name, "enum variant")?;
+
+    let mut generated = Vec::<GeneratedIcon>::new();
+    for icon in icons {
+        let (width, height, body) = get_icon_xml(icon, collections_cache, None)
+            .map_err(|e| format!("Could not read {}: {}", icon, e))?;
+
+        generated.push(GeneratedIcon {
+            id: icon.to_string(),
+            variant: component_name(icon),
+            width,
+            height,
+            body,
+        });
+    }
+
+    let mut source = HEADER.to_string();
+
+    source.push_str(match args.framework {
+        Framework::Leptos => "\nuse leptos::*;\n",
+        Framework::Yew => "\nuse yew::prelude::*;\n",
+        Framework::Dioxus => "\nuse dioxus::prelude::*;\n",
+        Framework::Egui => "\nuse std::borrow::Cow;\n\nuse egui::{load::Bytes, ImageSource};\n",
+        Framework::React
+        | Framework::Vue
+        | Framework::Svelte
+        | Framework::Solid
+        | Framework::WebComponent => unreachable!("not a Rust framework"),
+    });
+
+    source.push_str(&icon_enum(&generated));
+
+    match args.framework {
+        Framework::Leptos => source.push_str(&(ICON_IMPL.to_string() + LEPTOS_COMPONENT)),
+        Framework::Yew => source.push_str(&(ICON_IMPL.to_string() + YEW_COMPONENT)),
+        Framework::Dioxus => source.push_str(&(ICON_IMPL.to_string() + DIOXUS_COMPONENT)),
+        Framework::Egui => source.push_str(&egui_sources(&generated)),
+        Framework::React
+        | Framework::Vue
+        | Framework::Svelte
+        | Framework::Solid
+        | Framework::WebComponent => unreachable!("not a Rust framework"),
+    }
+
+    let path = args.out_dir.join(format!("{}.rs", args.module));
+    write_file(&path, source.as_bytes())?;
+    println!("{}", path.display());
+
+    Ok(())
+}
+
+/// Generates the `Icon` enum, with the identifier, view box and body of each icon.
+fn icon_enum(icons: &[GeneratedIcon]) -> String {
+    let match_arms = |value: &dyn Fn(&GeneratedIcon) -> String| {
+        icons
+            .iter()
+            .map(|icon| format!("            Icon::{} => {},\n", icon.variant, value(icon)))
+            .collect::<String>()
+    };
+
+    format!(
+        r#"
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Icon {{
+{variants}}}
+
+impl Icon {{
+    pub const ALL: &'static [Icon] = &[
+{all}    ];
+
+    /// The Iconify identifier of the icon.
+    pub fn id(self) -> &'static str {{
+        match self {{
+{ids}        }}
+    }}
+
+    pub fn view_box(self) -> &'static str {{
+        match self {{
+{view_boxes}        }}
+    }}
+
+    /// The SVG markup inside the root element of the icon.
+    pub fn body(self) -> &'static str {{
+        match self {{
+{bodies}        }}
+    }}
+}}
+"#,
+        variants = icons
+            .iter()
+            .map(|icon| format!("    {},\n", icon.variant))
+            .collect::<String>(),
+        all = icons
+            .iter()
+            .map(|icon| format!("        Icon::{},\n", icon.variant))
+            .collect::<String>(),
+        ids = match_arms(&|icon| format!("{:?}", icon.id)),
+        view_boxes = match_arms(&|icon| format!("\"0 0 {} {}\"", icon.width, icon.height)),
+        bodies = match_arms(&|icon| format!("{:?}", icon.body)),
+    )
+}
+
+/// Generates an `ImageSource` constant per icon, for the SVG loader of egui_extras.
+/// Monochrome icons are drawn in white, so they can be coloured with `Image::tint`.
+fn egui_sources(icons: &[GeneratedIcon]) -> String {
+    let options = SvgOptions {
+        color: "#fff".to_string(),
+        ..Default::default()
+    };
+
+    let mut source = String::new();
+    let mut arms = String::new();
+
+    for icon in icons {
+        let constant = icon
+            .id
+            .to_ascii_uppercase()
+            .replace(|c: char| !c.is_ascii_alphanumeric(), "_");
+        let svg = standalone_svg(icon.width, icon.height, &icon.body, &options);
+
+        source.push_str(&format!(
+            "\npub const {}: ImageSource<'static> = ImageSource::Bytes {{\n    uri: Cow::Borrowed(\"bytes://{}.svg\"),\n    bytes: Bytes::Static({:?}.as_bytes()),\n}};\n",
+            constant,
+            icon.id.replace(':', "-"),
+            svg
+        ));
+        arms.push_str(&format!(
+            "            Icon::{} => {},\n",
+            icon.variant, constant
+        ));
+    }
+
+    source.push_str(&format!(
+        r#"
+impl Icon {{
+    pub fn image_source(self) -> ImageSource<'static> {{
+        match self {{
+{}        }}
+    }}
+}}
+"#,
+        arms
+    ));
+
+    source
+}
@@ -0,0 +1,586 @@
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    time::Duration,
+};
+
+use log::info;
+use serde::Deserialize;
+
+use crate::{
+    config::Config,
+    enums::{SearchResponse, Themes},
+    files::{
+        fetch_api_icons, fetch_collection_themes, get_cached_themes, get_icon_xml, query,
+        write_cached_themes,
+    },
+    parser::{self, Symbol},
+    traits::IconTraits,
+};
+
+/// The maximum number of results the Iconify API returns for a single search.
+const REMOTE_SEARCH_LIMIT: usize = 999;
+
+/// The themes the browser cycles through when toggling the theme filter.
+pub const THEMES: [&str; 5] = ["outline", "filled", "rounded", "sharp", "duotone"];
+
+/// Searches for icons, either through an Iconify compatible API or the local
+/// icons cache. If the API can't be reached, the local cache is used instead.
+/// If a theme is given, only icons in that theme are returned.
+pub fn search(
+    query_string: &Option<String>,
+    prefix: &Option<String>,
+    theme: &Option<String>,
+    search_api: &Option<String>,
+    config: &Config,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let (query_string, prefix) = &expand_saved_searches(query_string, prefix, config)?;
+
+    // An invalid query is an error, rather than a reason to use the local cache.
+    if let Some(query_string) = query_string {
+        parser::parse(query_string)?;
+    }
+
+    let mut results = None;
+
+    if let Some(api) = search_api {
+        match remote_query(api, query_string, prefix) {
+            Ok(remote_results) => results = Some(remote_results),
+            // Not logged, as the default log level would hide it.
+            Err(e) => eprintln!("Remote search failed, using the local cache: {}", e),
+        }
+    }
+
+    let results = match results {
+        Some(results) => results,
+        None => query(query_string, prefix)?,
+    };
+
+    match theme {
+        Some(theme) => filter_by_theme(results, theme, search_api.as_deref()),
+        None => Ok(results),
+    }
+}
+
+/// Returns the page of results starting at `offset`, with at most `limit` results.
+/// The browser pages through results the same way, with a page per screen.
+pub fn page<T>(results: &[T], offset: usize, limit: Option<usize>) -> &[T] {
+    let start = offset.min(results.len());
+    let end = limit.map_or(results.len(), |limit| {
+        start.saturating_add(limit).min(results.len())
+    });
+
+    &results[start..end]
+}
+
+/// Describes which results are shown, e.g. "Showing 1-50 of 3000 icons."
+pub fn page_summary(offset: usize, shown: usize, total: usize) -> String {
+    if shown == 0 {
+        format!("Showing 0 of {} icons.", total)
+    } else {
+        format!(
+            "Showing {}-{} of {} icons.",
+            offset + 1,
+            offset + shown,
+            total
+        )
+    }
+}
+
+/// Narrows down a comma separated list of prefixes to the ones also in the other
+/// list. Returns `None` when the lists have no prefixes in common.
+fn intersect_prefixes(prefix: Option<&str>, other: &str) -> Option<String> {
+    let Some(prefix) = prefix else {
+        return Some(other.to_string());
+    };
+
+    let other: Vec<&str> = other.split(',').map(str::trim).collect();
+    let intersection: Vec<&str> = prefix
+        .split(',')
+        .map(str::trim)
+        .filter(|p| other.contains(p))
+        .collect();
+
+    Some(intersection.join(",")).filter(|prefixes| !prefixes.is_empty())
+}
+
+/// Expands the `@name` saved searches in the query. Saved queries are grouped,
+/// so they combine with the other terms, and saved prefixes narrow down the
+/// collections that are searched. As a saved search with only a prefix narrows
+/// down the whole search, it can't be negated, grouped or combined with `|`.
+pub fn expand_saved_searches(
+    query_string: &Option<String>,
+    prefix: &Option<String>,
+    config: &Config,
+) -> Result<(Option<String>, Option<String>), Box<dyn Error>> {
+    let Some(query_string) = query_string else {
+        return Ok((None, prefix.clone()));
+    };
+
+    let mut expanded = String::new();
+    let mut prefix = prefix.clone();
+    let mut prefix_only = None;
+    let mut has_or = false;
+    let mut depth = 0;
+    let mut rest = query_string.as_str();
+
+    while let Some(c) = rest.chars().next() {
+        rest = &rest[c.len_utf8()..];
+
+        match c {
+            '|' => has_or = true,
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => {}
+        }
+        if c != '@' {
+            expanded.push(c);
+            continue;
+        }
+
+        let name_end = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+            .unwrap_or(rest.len());
+        let name = &rest[..name_end];
+        rest = &rest[name_end..];
+
+        let Some(saved_search) = config.saved_search(name) else {
+            return Err(format!("Unknown saved search '@{}'.", name).into());
+        };
+
+        match saved_search.query() {
+            Some(saved_query) => expanded.push_str(&format!("({})", saved_query)),
+            None => {
+                let before = expanded.trim_end().chars().next_back();
+                let after = rest.trim_start().chars().next();
+
+                if depth > 0 || before == Some('!') || before == Some('|') || after == Some('|') {
+                    return Err(prefix_only_error(name));
+                }
+                prefix_only = Some(name);
+            }
+        }
+        if let Some(saved_prefix) = saved_search.prefix() {
+            let Some(intersection) = intersect_prefixes(prefix.as_deref(), saved_prefix) else {
+                return Err(format!(
+                    "None of the collections searched are in the saved search '@{}'.",
+                    name
+                )
+                .into());
+            };
+            prefix = Some(intersection);
+        }
+    }
+
+    if let (Some(name), true) = (prefix_only, has_or) {
+        return Err(prefix_only_error(name));
+    }
+
+    // Saved searches without a query leave operators and spaces behind.
+    let expanded = expanded.split_whitespace().collect::<Vec<&str>>().join(" ");
+    let expanded = expanded.trim_matches(|c: char| c == '&' || c == '|' || c.is_whitespace());
+
+    if expanded.is_empty() {
+        Ok((None, prefix))
+    } else {
+        Ok((Some(expanded.to_string()), prefix))
+    }
+}
+
+fn prefix_only_error(name: &str) -> Box<dyn Error> {
+    format!(
+        "The saved search '@{}' only selects collections, so it can't be negated, grouped or combined with |.",
+        name
+    )
+    .into()
+}
+
+/// Normalizes a theme name so that e.g. "Rounded" matches "round" and "Outlined" matches "outline".
+fn normalize_theme(theme: &str) -> String {
+    let theme = theme.to_lowercase();
+    let theme = theme
+        .strip_suffix('d')
+        .filter(|t| t.ends_with('e'))
+        .unwrap_or(&theme);
+    let theme = theme.strip_suffix('e').unwrap_or(theme);
+
+    match theme {
+        "solid" => "fill".to_string(),
+        "twoton" => "duoton".to_string(),
+        theme => theme.to_string(),
+    }
+}
+
+/// Finds the longest theme key that the icon name is in, falling back to the
+/// empty key which some collections use for their default theme.
+fn find_theme(
+    themes: &HashMap<String, String>,
+    in_theme: impl Fn(&str) -> bool,
+) -> Option<(&str, &str)> {
+    themes
+        .iter()
+        .map(|(key, title)| (key.trim_matches('-'), title.as_str()))
+        .filter(|(key, _)| !key.is_empty() && in_theme(key))
+        .max_by_key(|(key, _)| key.len())
+        .or_else(|| themes.get("").map(|title| ("", title.as_str())))
+}
+
+/// Checks if an icon belongs to the given theme, by the prefix and suffix themes of its collection.
+pub fn matches_theme(icon_name: &str, themes: &Themes, theme: &str) -> bool {
+    let theme = normalize_theme(theme);
+
+    let prefix_theme = themes.prefixes.as_ref().and_then(|prefixes| {
+        find_theme(prefixes, |key| icon_name.starts_with(&format!("{}-", key)))
+    });
+    let suffix_theme = themes
+        .suffixes
+        .as_ref()
+        .and_then(|suffixes| find_theme(suffixes, |key| icon_name.ends_with(&format!("-{}", key))));
+
+    [prefix_theme, suffix_theme]
+        .iter()
+        .flatten()
+        .any(|(key, title)| {
+            (!key.is_empty() && normalize_theme(key) == theme)
+                || normalize_theme(&title.replace(|c: char| !c.is_alphanumeric(), "")) == theme
+                || title
+                    .split(|c: char| !c.is_alphanumeric())
+                    .any(|word| normalize_theme(word) == theme)
+        })
+}
+
+/// Keeps the icons that belong to the given theme. The themes of collections
+/// missing from the cache are fetched and added to it, so this also works for
+/// remote search results without repeating the downloads.
+pub fn filter_by_theme(
+    icons: Vec<String>,
+    theme: &str,
+    api: Option<&str>,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut themes = get_cached_themes().unwrap_or_default();
+    let mut fetched = false;
+    let mut results = Vec::new();
+
+    for icon in icons {
+        let Some((collection_id, icon_name)) = icon.split_once(':') else {
+            continue;
+        };
+
+        if !themes.contains_key(collection_id) {
+            themes.insert(
+                collection_id.to_string(),
+                fetch_collection_themes(collection_id, api)?,
+            );
+            fetched = true;
+        }
+
+        if matches_theme(icon_name, &themes[collection_id], theme) {
+            results.push(icon);
+        }
+    }
+
+    if fetched {
+        write_cached_themes(&themes)?;
+    }
+
+    Ok(results)
+}
+
+/// Collects the phrases that a matching icon must contain at least one of.
+/// Phrases inside a negation are skipped, as they can't be searched for.
+fn collect_phrases(symbol: &Symbol, phrases: &mut Vec<String>) {
+    match symbol {
+        Symbol::Phrase(phrase) => phrases.push(phrase.to_string()),
+        Symbol::Group(group) => {
+            for symbol in group {
+                collect_phrases(symbol, phrases);
+            }
+        }
+        Symbol::And(left, right) | Symbol::Or(left, right) => {
+            collect_phrases(left, phrases);
+            collect_phrases(right, phrases);
+        }
+        Symbol::Filter(_, _) | Symbol::Not(_) => {}
+    }
+}
+
+fn fetch_search_results(
+    client: &reqwest::blocking::Client,
+    api: &str,
+    phrase: &str,
+    prefix: &Option<String>,
+) -> Result<SearchResponse, Box<dyn Error>> {
+    let limit = REMOTE_SEARCH_LIMIT.to_string();
+    let mut params = vec![("query", phrase), ("limit", limit.as_str())];
+
+    if let Some(prefix) = prefix {
+        if prefix.contains(',') {
+            params.push(("prefixes", prefix));
+        } else {
+            params.push(("prefix", prefix));
+        }
+    }
+
+    let response = client
+        .get(format!("{}/search", api.trim_end_matches('/')))
+        .query(&params)
+        .send()?
+        .error_for_status()?
+        .text()?;
+
+    let result: SearchResponse = serde_json::from_str(&response)?;
+
+    Ok(result)
+}
+
+/// The icons of a collection as listed by the API, by category.
+#[derive(Deserialize)]
+struct CollectionResponse {
+    uncategorized: Option<Vec<String>>,
+    categories: Option<HashMap<String, Vec<String>>>,
+}
+
+/// Lists the names of the icons in a collection through the API.
+fn fetch_collection_icons(
+    client: &reqwest::blocking::Client,
+    api: &str,
+    prefix: &str,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let response = client
+        .get(format!("{}/collection", api.trim_end_matches('/')))
+        .query(&[("prefix", prefix)])
+        .send()?
+        .error_for_status()?
+        .text()?;
+
+    let result: CollectionResponse = serde_json::from_str(&response)?;
+
+    Ok(result
+        .uncategorized
+        .into_iter()
+        .flatten()
+        .chain(
+            result
+                .categories
+                .into_iter()
+                .flat_map(|c| c.into_values().flatten()),
+        )
+        .collect())
+}
+
+/// Searches an Iconify compatible API. The API doesn't understand the boolean
+/// query syntax, so every phrase is searched for separately and the combined
+/// results are matched against the full query locally. Plain phrases are left
+/// to the API, which also matches on aliases and tags. Filters on the drawing
+/// traits are matched by analysing the bodies of the found icons.
+pub fn remote_query(
+    api: &str,
+    query_string: &Option<String>,
+    prefix: &Option<String>,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let Some(query_string) = query_string else {
+        // Listing collections only requires downloading those collections.
+        let Some(prefix) = prefix else {
+            return Err("No query or prefix to search for.".into());
+        };
+
+        let client = reqwest::blocking::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()?;
+
+        let mut icons = Vec::new();
+        for prefix in prefix.split(',').map(str::trim) {
+            let mut collection_icons: Vec<String> = fetch_collection_icons(&client, api, prefix)?
+                .into_iter()
+                .map(|icon| format!("{}:{}", prefix, icon))
+                .collect();
+            collection_icons.sort();
+            collection_icons.dedup();
+            icons.extend(collection_icons);
+        }
+
+        return Ok(icons);
+    };
+
+    let parsed_query = parser::parse(query_string)?;
+
+    let mut phrases = Vec::new();
+    collect_phrases(&parsed_query, &mut phrases);
+
+    if phrases.is_empty() {
+        return Err("Query has no phrases to search for.".into());
+    }
+
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()?;
+
+    let match_locally = !matches!(parsed_query, Symbol::Phrase(_));
+    let has_filters = parser::has_filters(&parsed_query);
+    let mut seen = HashSet::new();
+    let mut icons = Vec::new();
+
+    for phrase in phrases {
+        info!("Searching {} for '{}'..", api, phrase);
+        let response = fetch_search_results(&client, api, &phrase, prefix)?;
+        icons.extend(
+            response
+                .icons
+                .into_iter()
+                .filter(|icon| seen.insert(icon.clone())),
+        );
+    }
+
+    if !match_locally {
+        return Ok(icons);
+    }
+
+    // Filters need the icon bodies, which are fetched with a request per collection.
+    let mut collections_cache = HashMap::new();
+    if has_filters {
+        fetch_api_icons(api, &icons, &mut collections_cache)?;
+    }
+
+    let mut results = Vec::new();
+    for icon in icons {
+        let traits = if has_filters {
+            let (_, _, body) = get_icon_xml(&icon, &mut collections_cache, Some(api))?;
+            IconTraits::from_body(&body)
+        } else {
+            IconTraits::default()
+        };
+
+        if parser::match_query(&icon, &traits, &parsed_query)?.is_some() {
+            results.push(icon);
+        }
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SavedSearch;
+
+    fn config() -> Config {
+        let searches = HashMap::from([
+            (
+                "approved".to_string(),
+                SavedSearch::Search {
+                    query: None,
+                    prefix: Some("mdi,lucide".to_string()),
+                },
+            ),
+            (
+                "arrows".to_string(),
+                SavedSearch::Query("arrow | chevron".to_string()),
+            ),
+        ]);
+
+        Config {
+            searches: Some(searches),
+            ..Default::default()
+        }
+    }
+
+    fn expand(
+        query: &str,
+        prefix: Option<&str>,
+    ) -> Result<(Option<String>, Option<String>), Box<dyn Error>> {
+        expand_saved_searches(
+            &Some(query.to_string()),
+            &prefix.map(String::from),
+            &config(),
+        )
+    }
+
+    #[test]
+    fn expands_saved_queries_as_groups() {
+        let (query, prefix) = expand("@arrows & left", None).unwrap();
+
+        assert_eq!(query.as_deref(), Some("(arrow | chevron) & left"));
+        assert_eq!(prefix, None);
+    }
+
+    #[test]
+    fn saved_prefixes_narrow_down_the_collections() {
+        let (query, prefix) = expand("arrow & @approved", Some("mdi,logos")).unwrap();
+
+        assert_eq!(query.as_deref(), Some("arrow"));
+        assert_eq!(prefix.as_deref(), Some("mdi"));
+
+        let (query, prefix) = expand("@approved", None).unwrap();
+        assert_eq!(query, None);
+        assert_eq!(prefix.as_deref(), Some("mdi,lucide"));
+    }
+
+    #[test]
+    fn rejects_negated_prefix_only_searches() {
+        assert!(expand("arrow & !@approved", None).is_err());
+        assert!(expand("! @approved arrow", None).is_err());
+    }
+
+    #[test]
+    fn rejects_prefix_only_searches_with_operators() {
+        assert!(expand("arrow | @approved", None).is_err());
+        assert!(expand("@approved | arrow", None).is_err());
+        assert!(expand("@approved arrow | home", None).is_err());
+        assert!(expand("(arrow @approved)", None).is_err());
+    }
+
+    #[test]
+    fn rejects_saved_prefixes_outside_the_collections() {
+        let error = expand("arrow @approved", Some("logos"))
+            .unwrap_err()
+            .to_string();
+
+        assert_eq!(
+            error,
+            "None of the collections searched are in the saved search '@approved'."
+        );
+    }
+
+    #[test]
+    fn intersects_prefixes() {
+        assert_eq!(
+            intersect_prefixes(None, "mdi,lucide").as_deref(),
+            Some("mdi,lucide")
+        );
+        assert_eq!(
+            intersect_prefixes(Some("logos, mdi"), "mdi,lucide").as_deref(),
+            Some("mdi")
+        );
+        assert_eq!(intersect_prefixes(Some("logos"), "mdi,lucide"), None);
+    }
+
+    #[test]
+    fn rejects_unknown_saved_searches() {
+        let error = expand("@missing", None).unwrap_err().to_string();
+
+        assert_eq!(error, "Unknown saved search '@missing'.");
+    }
+
+    #[test]
+    fn pages_through_results() {
+        let results = [1, 2, 3, 4, 5];
+
+        assert_eq!(page(&results, 0, Some(2)), &[1, 2]);
+        assert_eq!(page(&results, 4, Some(2)), &[5]);
+        assert_eq!(page(&results, 2, None), &[3, 4, 5]);
+        assert_eq!(page(&results, 1, Some(0)), &[] as &[i32]);
+        assert_eq!(page(&results, 5, Some(2)), &[] as &[i32]);
+        assert_eq!(page(&results, 100, None), &[] as &[i32]);
+        assert_eq!(page(&results, 3, Some(usize::MAX)), &[4, 5]);
+    }
+
+    #[test]
+    fn summarizes_pages() {
+        assert_eq!(page_summary(0, 50, 3000), "Showing 1-50 of 3000 icons.");
+        assert_eq!(
+            page_summary(2990, 10, 3000),
+            "Showing 2991-3000 of 3000 icons."
+        );
+        assert_eq!(page_summary(5000, 0, 3000), "Showing 0 of 3000 icons.");
+    }
+}
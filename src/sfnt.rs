@@ -0,0 +1,723 @@
+//! A minimal writer for TrueType fonts with simple glyphs, and their WOFF2 wrapping.
+
+use std::error::Error;
+
+use brotli::{
+    enc::{backward_references::BrotliEncoderMode, BrotliEncoderParams},
+    BrotliCompress,
+};
+
+/// A point of a glyph outline in font units, and whether it is on the curve.
+pub type GlyphPoint = (i16, i16, bool);
+
+/// A glyph with its contours in font units. Off curve points are quadratic controls.
+#[derive(Clone, Debug, Default)]
+pub struct Glyph {
+    pub advance: u16,
+    pub contours: Vec<Vec<GlyphPoint>>,
+}
+
+impl Glyph {
+    /// The bounding box as (x min, y min, x max, y max), or zeros if the glyph is empty.
+    fn bounds(&self) -> (i16, i16, i16, i16) {
+        let mut points = self.contours.iter().flatten();
+
+        let Some(&(x, y, _)) = points.next() else {
+            return (0, 0, 0, 0);
+        };
+
+        points.fold((x, y, x, y), |(x0, y0, x1, y1), &(x, y, _)| {
+            (x0.min(x), y0.min(y), x1.max(x), y1.max(y))
+        })
+    }
+
+    fn point_count(&self) -> usize {
+        self.contours.iter().map(Vec::len).sum()
+    }
+}
+
+/// The vertical metrics and names of a font.
+pub struct FontInfo<'a> {
+    pub family: &'a str,
+    pub units_per_em: u16,
+    pub ascender: i16,
+    pub descender: i16,
+}
+
+trait PushBe {
+    fn u16(&mut self, value: u16);
+    fn i16(&mut self, value: i16);
+    fn u32(&mut self, value: u32);
+}
+
+impl PushBe for Vec<u8> {
+    fn u16(&mut self, value: u16) {
+        self.extend_from_slice(&value.to_be_bytes());
+    }
+
+    fn i16(&mut self, value: i16) {
+        self.extend_from_slice(&value.to_be_bytes());
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.extend_from_slice(&value.to_be_bytes());
+    }
+}
+
+type Table = ([u8; 4], Vec<u8>);
+
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+fn pad4(data: &mut Vec<u8>) {
+    data.resize(data.len().next_multiple_of(4), 0);
+}
+
+/// Builds a TrueType font and its WOFF2 version. Glyph 0 is added as `.notdef`,
+/// so the glyph ids in the character map start at 1.
+pub fn build_font(
+    info: &FontInfo,
+    glyphs: &[Glyph],
+    cmap: &[(u32, u16)],
+) -> Result<(Vec<u8>, Vec<u8>), Box<dyn Error>> {
+    let mut all_glyphs = vec![Glyph {
+        advance: info.units_per_em,
+        contours: vec![],
+    }];
+    all_glyphs.extend_from_slice(glyphs);
+
+    let mut cmap = cmap.to_vec();
+    cmap.sort();
+
+    let (glyf, loca) = glyf_and_loca(&all_glyphs);
+
+    let mut tables: Vec<Table> = vec![
+        (*b"OS/2", os2(info, &all_glyphs, &cmap)),
+        (*b"cmap", cmap_table(&cmap)),
+        (*b"glyf", glyf),
+        (*b"head", head(info, &all_glyphs)),
+        (*b"hhea", hhea(info, &all_glyphs)),
+        (*b"hmtx", hmtx(&all_glyphs)),
+        (*b"loca", loca),
+        (*b"maxp", maxp(&all_glyphs)),
+        (*b"name", name(info)),
+        (*b"post", post(info)),
+    ];
+
+    let ttf = write_ttf(&mut tables);
+    let woff2 = write_woff2(&tables, ttf.len())?;
+
+    Ok((ttf, woff2))
+}
+
+fn glyf_and_loca(glyphs: &[Glyph]) -> (Vec<u8>, Vec<u8>) {
+    let mut glyf = Vec::new();
+    let mut loca = Vec::new();
+
+    for glyph in glyphs {
+        loca.u32(glyf.len() as u32);
+
+        if glyph.contours.is_empty() {
+            continue;
+        }
+
+        let (x_min, y_min, x_max, y_max) = glyph.bounds();
+        glyf.i16(glyph.contours.len() as i16);
+        glyf.i16(x_min);
+        glyf.i16(y_min);
+        glyf.i16(x_max);
+        glyf.i16(y_max);
+
+        let mut end = 0;
+        for contour in &glyph.contours {
+            end += contour.len();
+            glyf.u16(end as u16 - 1);
+        }
+
+        // No instructions.
+        glyf.u16(0);
+
+        let points: Vec<&GlyphPoint> = glyph.contours.iter().flatten().collect();
+        for (_, _, on_curve) in &points {
+            glyf.push(u8::from(*on_curve));
+        }
+
+        // Coordinates are stored as deltas from the previous point, as 16 bit values.
+        let (mut x, mut y) = (0i16, 0i16);
+        for (px, _, _) in &points {
+            glyf.i16(px.wrapping_sub(x));
+            x = *px;
+        }
+        for (_, py, _) in &points {
+            glyf.i16(py.wrapping_sub(y));
+            y = *py;
+        }
+
+        pad4(&mut glyf);
+    }
+    loca.u32(glyf.len() as u32);
+
+    (glyf, loca)
+}
+
+fn head(info: &FontInfo, glyphs: &[Glyph]) -> Vec<u8> {
+    let (x_min, y_min, x_max, y_max) = font_bounds(glyphs);
+
+    let mut data = Vec::new();
+    data.u32(0x00010000);
+    data.u32(0x00010000); // font revision
+    data.u32(0); // checksum adjustment, filled in when writing the font
+    data.u32(0x5F0F3CF5);
+    data.u16(0b1011); // baseline and left sidebearing at 0, integer scaling
+    data.u16(info.units_per_em);
+    data.extend_from_slice(&[0; 16]); // created and modified
+    data.i16(x_min);
+    data.i16(y_min);
+    data.i16(x_max);
+    data.i16(y_max);
+    data.u16(0); // mac style
+    data.u16(8); // lowest readable size
+    data.i16(2); // font direction hint
+    data.i16(1); // long loca offsets
+    data.i16(0);
+
+    data
+}
+
+fn font_bounds(glyphs: &[Glyph]) -> (i16, i16, i16, i16) {
+    glyphs
+        .iter()
+        .filter(|glyph| !glyph.contours.is_empty())
+        .map(Glyph::bounds)
+        .reduce(|(a0, b0, c0, d0), (a1, b1, c1, d1)| {
+            (a0.min(a1), b0.min(b1), c0.max(c1), d0.max(d1))
+        })
+        .unwrap_or_default()
+}
+
+fn hhea(info: &FontInfo, glyphs: &[Glyph]) -> Vec<u8> {
+    let drawn = || glyphs.iter().filter(|glyph| !glyph.contours.is_empty());
+
+    let mut data = Vec::new();
+    data.u32(0x00010000);
+    data.i16(info.ascender);
+    data.i16(info.descender);
+    data.i16(0); // line gap
+    data.u16(glyphs.iter().map(|glyph| glyph.advance).max().unwrap_or(0));
+    data.i16(drawn().map(|glyph| glyph.bounds().0).min().unwrap_or(0));
+    data.i16(
+        drawn()
+            .map(|glyph| glyph.advance as i16 - glyph.bounds().2)
+            .min()
+            .unwrap_or(0),
+    );
+    data.i16(drawn().map(|glyph| glyph.bounds().2).max().unwrap_or(0));
+    data.i16(1); // caret slope rise
+    data.i16(0); // caret slope run
+    data.i16(0); // caret offset
+    data.extend_from_slice(&[0; 8]);
+    data.i16(0); // metric data format
+    data.u16(glyphs.len() as u16);
+
+    data
+}
+
+fn hmtx(glyphs: &[Glyph]) -> Vec<u8> {
+    let mut data = Vec::new();
+
+    for glyph in glyphs {
+        data.u16(glyph.advance);
+        data.i16(glyph.bounds().0);
+    }
+
+    data
+}
+
+fn maxp(glyphs: &[Glyph]) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.u32(0x00010000);
+    data.u16(glyphs.len() as u16);
+    data.u16(glyphs.iter().map(Glyph::point_count).max().unwrap_or(0) as u16);
+    data.u16(
+        glyphs
+            .iter()
+            .map(|glyph| glyph.contours.len())
+            .max()
+            .unwrap_or(0) as u16,
+    );
+    data.u16(0); // composite points
+    data.u16(0); // composite contours
+    data.u16(2); // zones
+    data.extend_from_slice(&[0; 18]);
+
+    data
+}
+
+fn os2(info: &FontInfo, glyphs: &[Glyph], cmap: &[(u32, u16)]) -> Vec<u8> {
+    let em = info.units_per_em as i32;
+    let scaled = |ratio: i32| (em * ratio / 100) as i16;
+    let (_, y_min, _, y_max) = font_bounds(glyphs);
+
+    let drawn: Vec<&Glyph> = glyphs[1..].iter().collect();
+    let average_width = drawn
+        .iter()
+        .map(|glyph| glyph.advance as usize)
+        .sum::<usize>()
+        .checked_div(drawn.len())
+        .unwrap_or(em as usize);
+
+    let first = cmap.first().map_or(0, |(code, _)| *code).min(0xFFFF) as u16;
+    let last = cmap.last().map_or(0, |(code, _)| *code).min(0xFFFF) as u16;
+
+    let mut data = Vec::new();
+    data.u16(4);
+    data.i16(average_width as i16);
+    data.u16(400); // weight
+    data.u16(5); // width
+    data.u16(0); // embedding permissions
+    for value in [65, 70, 0, 14, 65, 70, 0, 48, 5, 25] {
+        // Sub- and superscript sizes and offsets, then the strikeout.
+        data.i16(scaled(value));
+    }
+    data.i16(0); // family class
+    data.extend_from_slice(&[0; 10]); // panose
+    data.u32(0);
+    data.u32(1 << 28); // private use area
+    data.u32(0);
+    data.u32(0);
+    data.extend_from_slice(b"NONE");
+    data.u16(0x00C0); // regular, use typographic metrics
+    data.u16(first);
+    data.u16(last);
+    data.i16(info.ascender);
+    data.i16(info.descender);
+    data.i16(0); // line gap
+    data.u16(info.ascender.max(y_max) as u16);
+    data.u16((-info.descender).max(-y_min) as u16);
+    data.u32(1); // latin 1 code page
+    data.u32(0);
+    data.i16(0); // x height
+    data.i16(0); // cap height
+    data.u16(0); // default character
+    data.u16(32); // break character
+    data.u16(0); // max context
+
+    data
+}
+
+/// The character map, with a format 4 subtable for the Basic Multilingual Plane and
+/// a format 12 subtable when there are characters beyond it.
+fn cmap_table(cmap: &[(u32, u16)]) -> Vec<u8> {
+    let bmp: Vec<&(u32, u16)> = cmap.iter().filter(|(code, _)| *code <= 0xFFFF).collect();
+
+    // One segment per character, and the final segment for 0xFFFF.
+    let segments = bmp.len() + 1;
+    let search_range = 2 * (1 << segments.ilog2());
+
+    let mut format4 = Vec::new();
+    format4.u16(4);
+    format4.u16((16 + segments * 8) as u16);
+    format4.u16(0); // language
+    format4.u16(segments as u16 * 2);
+    format4.u16(search_range as u16);
+    format4.u16(segments.ilog2() as u16);
+    format4.u16((segments * 2 - search_range) as u16);
+    for (code, _) in &bmp {
+        format4.u16(*code as u16);
+    }
+    format4.u16(0xFFFF);
+    format4.u16(0); // reserved
+    for (code, _) in &bmp {
+        format4.u16(*code as u16);
+    }
+    format4.u16(0xFFFF);
+    for (code, glyph) in &bmp {
+        format4.u16(glyph.wrapping_sub(*code as u16));
+    }
+    format4.u16(1);
+    for _ in 0..segments {
+        format4.u16(0); // range offsets
+    }
+
+    let mut subtables = vec![(1u16, format4)];
+
+    if cmap.iter().any(|(code, _)| *code > 0xFFFF) {
+        let mut format12 = Vec::new();
+        format12.u16(12);
+        format12.u16(0);
+        format12.u32((16 + cmap.len() * 12) as u32);
+        format12.u32(0); // language
+        format12.u32(cmap.len() as u32);
+        for (code, glyph) in cmap {
+            format12.u32(*code);
+            format12.u32(*code);
+            format12.u32(*glyph as u32);
+        }
+
+        subtables.push((10, format12));
+    }
+
+    let mut data = Vec::new();
+    data.u16(0);
+    data.u16(subtables.len() as u16);
+
+    let mut offset = 4 + subtables.len() * 8;
+    for (encoding, subtable) in &subtables {
+        data.u16(3); // windows
+        data.u16(*encoding);
+        data.u32(offset as u32);
+        offset += subtable.len();
+    }
+    for (_, subtable) in subtables {
+        data.extend(subtable);
+    }
+
+    data
+}
+
+fn name(info: &FontInfo) -> Vec<u8> {
+    let postscript_name: String = info
+        .family
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect();
+
+    let names = [
+        (1, info.family.to_string()),
+        (2, "Regular".to_string()),
+        (3, format!("{}:Version 1.0", postscript_name)),
+        (4, info.family.to_string()),
+        (5, "Version 1.0".to_string()),
+        (6, postscript_name),
+    ];
+
+    let mut strings = Vec::new();
+    let mut data = Vec::new();
+    data.u16(0);
+    data.u16(names.len() as u16);
+    data.u16((6 + names.len() * 12) as u16);
+
+    for (id, value) in names {
+        let encoded: Vec<u8> = value.encode_utf16().flat_map(u16::to_be_bytes).collect();
+
+        data.u16(3); // windows
+        data.u16(1); // unicode
+        data.u16(0x0409); // english
+        data.u16(id);
+        data.u16(encoded.len() as u16);
+        data.u16(strings.len() as u16);
+        strings.extend(encoded);
+    }
+    data.extend(strings);
+
+    data
+}
+
+fn post(info: &FontInfo) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.u32(0x00030000); // no glyph names
+    data.u32(0); // italic angle
+    data.i16(-(info.units_per_em as i16) / 10); // underline position
+    data.i16(info.units_per_em as i16 / 20); // underline thickness
+    data.extend_from_slice(&[0; 20]);
+
+    data
+}
+
+/// Writes the tables as a TrueType font, filling in the checksum adjustment of the
+/// head table. The tables must be sorted by tag.
+fn write_ttf(tables: &mut [Table]) -> Vec<u8> {
+    let count = tables.len();
+    let search_range = 16 * (1 << count.ilog2());
+
+    let mut font = Vec::new();
+    font.u32(0x00010000);
+    font.u16(count as u16);
+    font.u16(search_range as u16);
+    font.u16(count.ilog2() as u16);
+    font.u16((count * 16 - search_range) as u16);
+
+    let mut offset = 12 + count * 16;
+    for (tag, data) in tables.iter() {
+        font.extend_from_slice(tag);
+        font.u32(checksum(data));
+        font.u32(offset as u32);
+        font.u32(data.len() as u32);
+        offset += data.len().next_multiple_of(4);
+    }
+
+    let head_offset = offset_of(tables, b"head", 12 + count * 16);
+    for (_, data) in tables.iter() {
+        font.extend_from_slice(data);
+        pad4(&mut font);
+    }
+
+    let adjustment = 0xB1B0AFBAu32.wrapping_sub(checksum(&font));
+    font[head_offset + 8..head_offset + 12].copy_from_slice(&adjustment.to_be_bytes());
+    if let Some((_, head)) = tables.iter_mut().find(|(tag, _)| tag == b"head") {
+        head[8..12].copy_from_slice(&adjustment.to_be_bytes());
+    }
+
+    font
+}
+
+fn offset_of(tables: &[Table], wanted: &[u8; 4], start: usize) -> usize {
+    let mut offset = start;
+
+    for (tag, data) in tables {
+        if tag == wanted {
+            break;
+        }
+        offset += data.len().next_multiple_of(4);
+    }
+
+    offset
+}
+
+/// The tags WOFF2 can refer to by index, in order.
+const WOFF2_KNOWN_TAGS: [&[u8; 4]; 12] = [
+    b"cmap", b"head", b"hhea", b"hmtx", b"maxp", b"name", b"OS/2", b"post", b"cvt ", b"fpgm",
+    b"glyf", b"loca",
+];
+
+fn push_base128(data: &mut Vec<u8>, value: u32) {
+    let mut bytes = vec![(value & 0x7F) as u8];
+    let mut rest = value >> 7;
+
+    while rest > 0 {
+        bytes.push((rest & 0x7F) as u8 | 0x80);
+        rest >>= 7;
+    }
+
+    data.extend(bytes.iter().rev());
+}
+
+/// Writes the tables as a WOFF2 font. None of the tables are transformed, and the
+/// data is compressed as a single Brotli stream.
+fn write_woff2(tables: &[Table], sfnt_size: usize) -> Result<Vec<u8>, Box<dyn Error>> {
+    // The loca table has to follow the glyf table.
+    let mut ordered: Vec<&Table> = tables.iter().filter(|(tag, _)| tag != b"loca").collect();
+    if let Some(glyf) = ordered.iter().position(|(tag, _)| tag == b"glyf") {
+        if let Some(loca) = tables.iter().find(|(tag, _)| tag == b"loca") {
+            ordered.insert(glyf + 1, loca);
+        }
+    }
+
+    let mut directory = Vec::new();
+    let mut stream = Vec::new();
+    for (tag, data) in &ordered {
+        let known = WOFF2_KNOWN_TAGS.iter().position(|known| *known == tag);
+
+        // Version 3 is the null transform for glyf and loca, version 0 for the others.
+        let transform = if tag == b"glyf" || tag == b"loca" {
+            3 << 6
+        } else {
+            0
+        };
+
+        match known {
+            Some(index) => directory.push(index as u8 | transform),
+            None => {
+                directory.push(63 | transform);
+                directory.extend_from_slice(tag);
+            }
+        }
+        push_base128(&mut directory, data.len() as u32);
+
+        stream.extend_from_slice(data);
+    }
+
+    let compressed = brotli_compress(&stream)?;
+
+    let length = (48 + directory.len() + compressed.len()).next_multiple_of(4);
+
+    let mut font = Vec::new();
+    font.extend_from_slice(b"wOF2");
+    font.u32(0x00010000);
+    font.u32(length as u32);
+    font.u16(tables.len() as u16);
+    font.u16(0);
+    font.u32(sfnt_size as u32);
+    font.u32(compressed.len() as u32);
+    font.u16(1); // major version
+    font.u16(0); // minor version
+    font.extend_from_slice(&[0; 20]); // metadata and private data
+    font.extend(directory);
+    font.extend(compressed);
+    pad4(&mut font);
+
+    Ok(font)
+}
+
+/// Compresses the table data with Brotli, in its font mode at the best quality.
+fn brotli_compress(data: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let params = BrotliEncoderParams {
+        quality: 11,
+        lgwin: 22,
+        mode: BrotliEncoderMode::BROTLI_MODE_FONT,
+        size_hint: data.len(),
+        ..Default::default()
+    };
+
+    let mut compressed = Vec::new();
+    BrotliCompress(&mut &data[..], &mut compressed, &params)?;
+
+    Ok(compressed)
+}
+
+#[cfg(test)]
+mod tests {
+    use ttf_parser::{Face, GlyphId, RawFace, Tag};
+
+    use super::*;
+
+    const INFO: FontInfo = FontInfo {
+        family: "Test Icons",
+        units_per_em: 1000,
+        ascender: 850,
+        descender: -150,
+    };
+
+    fn build() -> (Vec<u8>, Vec<u8>) {
+        let square = Glyph {
+            advance: 1000,
+            contours: vec![vec![
+                (0, -150, true),
+                (0, 850, true),
+                (1000, 850, true),
+                (1000, -150, true),
+            ]],
+        };
+        let triangle = Glyph {
+            advance: 500,
+            contours: vec![vec![(0, 0, true), (250, 500, false), (500, 0, true)]],
+        };
+
+        build_font(&INFO, &[square, triangle], &[(0x1F600, 2), (0xE000, 1)]).unwrap()
+    }
+
+    #[test]
+    fn ttf_round_trips() {
+        let (ttf, _) = build();
+        let face = Face::parse(&ttf, 0).unwrap();
+
+        assert_eq!(face.number_of_glyphs(), 3);
+        assert_eq!(face.units_per_em(), 1000);
+        assert_eq!(face.ascender(), 850);
+        assert_eq!(face.descender(), -150);
+        assert_eq!(face.glyph_index('\u{E000}'), Some(GlyphId(1)));
+        assert_eq!(face.glyph_index('\u{1F600}'), Some(GlyphId(2)));
+        assert_eq!(face.glyph_index('a'), None);
+        assert_eq!(face.glyph_hor_advance(GlyphId(2)), Some(500));
+
+        let bounds = face.glyph_bounding_box(GlyphId(1)).unwrap();
+        assert_eq!(
+            (bounds.x_min, bounds.y_min, bounds.x_max, bounds.y_max),
+            (0, -150, 1000, 850)
+        );
+        // The bounds include the control point of the curve.
+        let bounds = face.glyph_bounding_box(GlyphId(2)).unwrap();
+        assert_eq!(
+            (bounds.x_min, bounds.y_min, bounds.x_max, bounds.y_max),
+            (0, 0, 500, 500)
+        );
+        assert!(face.glyph_bounding_box(GlyphId(0)).is_none());
+
+        let family = face
+            .names()
+            .into_iter()
+            .find(|name| name.name_id == ttf_parser::name_id::FAMILY)
+            .and_then(|name| name.to_string());
+        assert_eq!(family.as_deref(), Some("Test Icons"));
+    }
+
+    #[test]
+    fn ttf_checksums_are_valid() {
+        let (ttf, _) = build();
+        let raw = RawFace::parse(&ttf, 0).unwrap();
+
+        assert_eq!(raw.table_records.len(), 10);
+        for record in raw.table_records {
+            let start = record.offset as usize;
+            let mut data = ttf[start..start + record.length as usize].to_vec();
+
+            // The head table is summed with its checksum adjustment set to zero.
+            if record.tag == Tag::from_bytes(b"head") {
+                data[8..12].fill(0);
+            }
+
+            assert_eq!(checksum(&data), record.check_sum, "{}", record.tag);
+            assert_eq!(start % 4, 0);
+        }
+
+        assert_eq!(checksum(&ttf), 0xB1B0AFBA);
+    }
+
+    #[test]
+    fn woff2_round_trips() {
+        let (ttf, woff2) = build();
+        let raw = RawFace::parse(&ttf, 0).unwrap();
+        let u32_at =
+            |offset: usize| u32::from_be_bytes(woff2[offset..offset + 4].try_into().unwrap());
+
+        assert_eq!(&woff2[..4], b"wOF2");
+        assert_eq!(u32_at(8) as usize, woff2.len());
+        assert_eq!(u16::from_be_bytes([woff2[12], woff2[13]]), 10);
+        assert_eq!(u32_at(16) as usize, ttf.len());
+
+        // Read the table directory, which holds the tag and length of each table.
+        let mut offset = 48;
+        let mut tables = Vec::<([u8; 4], usize)>::new();
+        for _ in 0..10 {
+            let flags = woff2[offset];
+            offset += 1;
+
+            let tag = match (flags & 63) as usize {
+                63 => {
+                    offset += 4;
+                    woff2[offset - 4..offset].try_into().unwrap()
+                }
+                index => *WOFF2_KNOWN_TAGS[index],
+            };
+
+            let mut length = 0;
+            loop {
+                let byte = woff2[offset];
+                offset += 1;
+                length = (length << 7) | (byte & 0x7F) as usize;
+                if byte & 0x80 == 0 {
+                    break;
+                }
+            }
+            tables.push((tag, length));
+        }
+
+        let compressed_length = u32_at(20) as usize;
+        let mut stream = Vec::new();
+        brotli::BrotliDecompress(&mut &woff2[offset..offset + compressed_length], &mut stream)
+            .unwrap();
+        assert_eq!(
+            stream.len(),
+            tables.iter().map(|(_, length)| length).sum::<usize>()
+        );
+        assert!(compressed_length < stream.len());
+
+        let mut start = 0;
+        for (tag, length) in tables {
+            let record = raw
+                .table_records
+                .into_iter()
+                .find(|record| record.tag == Tag::from_bytes(&tag))
+                .unwrap();
+            let table = &ttf[record.offset as usize..][..record.length as usize];
+
+            assert_eq!(&stream[start..start + length], table, "{}", record.tag);
+            start += length;
+        }
+    }
+}
@@ -0,0 +1,320 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    io::{stdout, IsTerminal},
+};
+
+use icon_core::resolve_alias;
+use resvg::usvg::fontdb::Database;
+use serde::Serialize;
+
+use crate::{
+    cli::ShowArgs,
+    enums::{Author, IconCollection, License, Themes},
+    files::{get_icon_xml, preview},
+    svg::{standalone_svg, SvgOptions},
+};
+
+/// Everything known about an icon, as printed by the show command.
+#[derive(Debug, Serialize)]
+pub struct IconDetails {
+    pub id: String,
+    pub collection: String,
+    pub author: Author,
+    pub license: License,
+    pub width: usize,
+    pub height: usize,
+    pub aliases: Vec<String>,
+    pub categories: Vec<String>,
+    /// The icons of the same shape in the themes of the collection.
+    pub themes: Vec<ThemeVariant>,
+    pub svg: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ThemeVariant {
+    pub theme: String,
+    pub icon: String,
+}
+
+/// Finds the icons of the same shape in the other themes of the collection, e.g.
+/// `home-outline` for `home`. Icons without a theme key belong to the theme
+/// with an empty key, if the collection has one.
+fn theme_variants(name: &str, collection: &IconCollection) -> Vec<(String, String)> {
+    let themes = Themes::from(collection);
+    let mut variants = Vec::new();
+
+    let affixes = [
+        (themes.prefixes.unwrap_or_default(), true),
+        (themes.suffixes.unwrap_or_default(), false),
+    ];
+
+    for (affixes, is_prefix) in affixes {
+        let affixed = |key: &str, base: &str| match (key.trim_matches('-'), is_prefix) {
+            ("", _) => base.to_string(),
+            (key, true) => format!("{}-{}", key, base),
+            (key, false) => format!("{}-{}", base, key),
+        };
+
+        // The longest key the name starts or ends with, as keys may overlap.
+        let base = affixes
+            .keys()
+            .map(|key| key.trim_matches('-'))
+            .filter(|key| !key.is_empty())
+            .filter_map(|key| match is_prefix {
+                true => name.strip_prefix(&format!("{}-", key)),
+                false => name.strip_suffix(&format!("-{}", key)),
+            })
+            .min_by_key(|base| base.len())
+            .unwrap_or(name);
+
+        let mut found: Vec<(String, String)> = affixes
+            .iter()
+            .map(|(key, title)| (title.clone(), affixed(key, base)))
+            .filter(|(_, variant)| collection.icons.contains_key(variant))
+            .collect();
+        found.sort();
+        variants.extend(found);
+    }
+
+    variants
+}
+
+impl IconDetails {
+    pub fn new(
+        icon: &str,
+        collections_cache: &mut HashMap<String, IconCollection>,
+    ) -> Result<IconDetails, Box<dyn Error>> {
+        let (width, height, body) = get_icon_xml(icon, collections_cache, None)?;
+        let (prefix, name) = icon.split_once(':').ok_or("Invalid icon identifier.")?;
+
+        // Looking up the icon has put its collection in the cache.
+        let collection = &collections_cache[prefix];
+        let info = &collection.info;
+
+        // The details of an alias are those of the icon it stands for.
+        let icon_name = resolve_alias(collection, name).unwrap_or(name);
+
+        let mut aliases: Vec<String> = collection
+            .aliases
+            .iter()
+            .flatten()
+            .map(|(alias, _)| alias)
+            .filter(|alias| *alias != name && resolve_alias(collection, alias) == Some(icon_name))
+            .map(|alias| format!("{}:{}", prefix, alias))
+            .collect();
+        aliases.sort();
+
+        let mut categories: Vec<String> = collection
+            .categories
+            .iter()
+            .flatten()
+            .filter(|(_, icons)| icons.iter().any(|icon| icon == icon_name))
+            .map(|(category, _)| category.clone())
+            .collect();
+        categories.sort();
+
+        let themes = theme_variants(icon_name, collection)
+            .into_iter()
+            .map(|(theme, variant)| ThemeVariant {
+                theme,
+                icon: format!("{}:{}", prefix, variant),
+            })
+            .collect();
+
+        Ok(IconDetails {
+            id: icon.to_string(),
+            collection: format!("{} ({})", info.name, prefix),
+            author: info.author.clone(),
+            license: info.license.clone(),
+            width,
+            height,
+            aliases,
+            categories,
+            themes,
+            svg: standalone_svg(width, height, &body, &SvgOptions::default()),
+        })
+    }
+}
+
+/// Prints the identifier, a preview and the license of an icon. The preview is
+/// left out when not printing to a terminal.
+pub fn print_summary(
+    icon: &str,
+    collections_cache: &mut HashMap<String, IconCollection>,
+    api: Option<&str>,
+    fontdb: &mut Database,
+) -> Result<(), Box<dyn Error>> {
+    println!("{}", icon);
+
+    if stdout().is_terminal() {
+        println!();
+        print!("  ");
+        preview(icon, collections_cache, api, fontdb)?;
+        println!();
+    }
+
+    if let Some((collection_id, _icon_id)) = icon.split_once(':') {
+        get_icon_xml(icon, collections_cache, api)?;
+        let license = &collections_cache[collection_id].info.license;
+        print!("License: {}", license.title);
+        if let Some(license_url) = &license.url {
+            println!(": {}", license_url);
+        } else {
+            println!();
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints the details of an icon, or them as JSON.
+pub fn show(
+    args: &ShowArgs,
+    collections_cache: &mut HashMap<String, IconCollection>,
+    fontdb: &mut Database,
+) -> Result<(), Box<dyn Error>> {
+    let details = IconDetails::new(&args.icon, collections_cache)?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&details)?);
+        return Ok(());
+    }
+
+    print_summary(&args.icon, collections_cache, None, fontdb)?;
+    println!("Collection: {}", details.collection);
+    print!("Author: {}", details.author.name);
+    if let Some(author_url) = &details.author.url {
+        println!(" ({})", author_url);
+    } else {
+        println!();
+    }
+    println!("Size: {}x{}", details.width, details.height);
+
+    if !details.aliases.is_empty() {
+        println!("Aliases: {}", details.aliases.join(", "));
+    }
+    if !details.categories.is_empty() {
+        println!("Categories: {}", details.categories.join(", "));
+    }
+    if !details.themes.is_empty() {
+        let themes: Vec<String> = details
+            .themes
+            .iter()
+            .map(|variant| format!("{}: {}", variant.theme, variant.icon))
+            .collect();
+        println!("Themes: {}", themes.join(", "));
+    }
+
+    println!();
+    println!("{}", details.svg);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn collection(themes: serde_json::Value) -> IconCollection {
+        let mut collection = json!({
+            "prefix": "mdi",
+            "width": 24,
+            "height": 24,
+            "lastModified": 0,
+            "info": {
+                "name": "Material Design Icons",
+                "total": 5,
+                "author": { "name": "Pictogrammers" },
+                "license": { "title": "Apache 2.0", "spdx": "Apache-2.0" },
+                "samples": [],
+                "palette": false
+            },
+            "icons": {
+                "home": { "body": "<path d=\"M10 20v-6h4v6\"/>" },
+                "home-outline": { "body": "<path d=\"M10 20v-6\"/>" },
+                "home-sharp-outline": { "body": "<path d=\"M10 20\"/>" },
+                "fa-home": { "body": "<path d=\"M4 20\"/>" },
+                "fa-regular-home": { "body": "<path d=\"M4 10\"/>" }
+            },
+            "aliases": {
+                "house": { "parent": "home" },
+                "cottage": { "parent": "house" },
+                "house-outline": { "parent": "home-outline" },
+                "loop-a": { "parent": "loop-b" },
+                "loop-b": { "parent": "loop-a" }
+            },
+            "categories": { "Home": ["home", "home-outline"] }
+        });
+        collection
+            .as_object_mut()
+            .unwrap()
+            .extend(themes.as_object().unwrap().clone());
+
+        serde_json::from_value(collection).unwrap()
+    }
+
+    fn assert_variants(name: &str, collection: &IconCollection, expected: &[(&str, &str)]) {
+        let variants = theme_variants(name, collection);
+        let variants: Vec<(&str, &str)> = variants
+            .iter()
+            .map(|(theme, icon)| (theme.as_str(), icon.as_str()))
+            .collect();
+
+        assert_eq!(variants, expected);
+    }
+
+    #[test]
+    fn theme_variants_use_the_longest_overlapping_suffix() {
+        let collection = collection(json!({
+            "suffixes": { "": "Filled", "outline": "Outline", "sharp-outline": "Sharp outline" }
+        }));
+        let expected = [
+            ("Filled", "home"),
+            ("Outline", "home-outline"),
+            ("Sharp outline", "home-sharp-outline"),
+        ];
+
+        assert_variants("home-sharp-outline", &collection, &expected);
+        assert_variants("home-outline", &collection, &expected);
+        assert_variants("home", &collection, &expected);
+    }
+
+    #[test]
+    fn theme_variants_use_the_longest_overlapping_prefix() {
+        let collection = collection(json!({
+            "prefixes": { "fa": "Solid", "fa-regular": "Regular" }
+        }));
+        let expected = [("Regular", "fa-regular-home"), ("Solid", "fa-home")];
+
+        assert_variants("fa-regular-home", &collection, &expected);
+        assert_variants("fa-home", &collection, &expected);
+    }
+
+    #[test]
+    fn aliases_are_resolved_through_chains() {
+        let collection = collection(json!({}));
+
+        assert_eq!(resolve_alias(&collection, "home"), Some("home"));
+        assert_eq!(resolve_alias(&collection, "cottage"), Some("home"));
+        assert_eq!(resolve_alias(&collection, "loop-a"), None);
+        assert_eq!(resolve_alias(&collection, "missing"), None);
+    }
+
+    #[test]
+    fn details_list_chained_aliases() {
+        let mut collections_cache = HashMap::from([("mdi".to_string(), collection(json!({})))]);
+
+        let details = IconDetails::new("mdi:home", &mut collections_cache).unwrap();
+        assert_eq!(details.aliases, ["mdi:cottage", "mdi:house"]);
+        assert_eq!(details.categories, ["Home"]);
+
+        let details = IconDetails::new("mdi:cottage", &mut collections_cache).unwrap();
+        assert_eq!(details.id, "mdi:cottage");
+        assert_eq!(details.aliases, ["mdi:house"]);
+        assert_eq!(details.categories, ["Home"]);
+        assert!(details.svg.contains("M10 20v-6h4v6"));
+    }
+}
@@ -0,0 +1,241 @@
+use std::{collections::HashMap, error::Error, path::Path};
+
+use resvg::{tiny_skia::Pixmap, usvg::fontdb::Database};
+
+use crate::{
+    enums::IconCollection,
+    files::{get_cached_hashes, get_icon_xml, icon_svg_document, render_svg},
+};
+
+/// A perceptual hash of the shape of an icon, one bit per cell of a 16x16 grid.
+pub type IconHash = [u64; 4];
+
+const GRID: usize = 16;
+
+/// The number of similar icons that are shown.
+pub const SIMILAR_RESULTS: usize = 24;
+
+/// The size icons are rendered at before hashing.
+const RENDER_SIZE: u32 = 64;
+
+/// How much of the shape of an image covers each pixel, from 0 to 1.
+struct InkMap {
+    width: usize,
+    height: usize,
+    ink: Vec<f32>,
+}
+
+impl InkMap {
+    /// Reads the ink from RGBA pixels. Transparent images are inked by their alpha,
+    /// opaque ones by how much each pixel differs from the background colour.
+    fn from_rgba(width: usize, height: usize, rgba: &[u8]) -> InkMap {
+        let luminance = |i: usize| {
+            (0.299 * rgba[i * 4] as f32
+                + 0.587 * rgba[i * 4 + 1] as f32
+                + 0.114 * rgba[i * 4 + 2] as f32)
+                / 255.0
+        };
+
+        let opaque = rgba.chunks_exact(4).all(|pixel| pixel[3] == 255);
+
+        let ink = if opaque {
+            let corners = [0, width - 1, (height - 1) * width, height * width - 1];
+            let background = corners.iter().map(|&i| luminance(i)).sum::<f32>() / 4.0;
+
+            (0..width * height)
+                .map(|i| (luminance(i) - background).abs())
+                .collect()
+        } else {
+            rgba.chunks_exact(4)
+                .map(|pixel| pixel[3] as f32 / 255.0)
+                .collect()
+        };
+
+        InkMap { width, height, ink }
+    }
+
+    fn from_pixmap(pixmap: &Pixmap) -> InkMap {
+        InkMap::from_rgba(
+            pixmap.width() as usize,
+            pixmap.height() as usize,
+            pixmap.data(),
+        )
+    }
+
+    /// Finds the bounding box of the inked pixels, as (left, top, right, bottom).
+    fn bounding_box(&self) -> Option<(usize, usize, usize, usize)> {
+        let mut bounds: Option<(usize, usize, usize, usize)> = None;
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.ink[y * self.width + x] < 0.25 {
+                    continue;
+                }
+
+                bounds = Some(match bounds {
+                    None => (x, y, x + 1, y + 1),
+                    Some((l, t, r, b)) => (l.min(x), t.min(y), r.max(x + 1), b.max(y + 1)),
+                });
+            }
+        }
+
+        bounds
+    }
+
+    /// Hashes the shape within its bounding box, so that padding and position
+    /// don't matter. The box is squared so the aspect ratio is kept.
+    fn hash(&self) -> IconHash {
+        let mut hash = [0; 4];
+
+        let Some((left, top, right, bottom)) = self.bounding_box() else {
+            return hash;
+        };
+
+        let side = (right - left).max(bottom - top) as f32;
+        let origin_x = (left + right) as f32 / 2.0 - side / 2.0;
+        let origin_y = (top + bottom) as f32 / 2.0 - side / 2.0;
+        let cell = side / GRID as f32;
+
+        let mut cells = [0.0; GRID * GRID];
+        for (i, value) in cells.iter_mut().enumerate() {
+            let x0 = origin_x + (i % GRID) as f32 * cell;
+            let y0 = origin_y + (i / GRID) as f32 * cell;
+
+            let mut sum = 0.0;
+            let mut samples = 0;
+            // Sample at least one pixel, even when the shape is smaller than the grid.
+            let steps = (cell.ceil() as usize).max(1);
+            for sy in 0..steps {
+                for sx in 0..steps {
+                    let x = (x0 + (sx as f32 + 0.5) * cell / steps as f32).floor();
+                    let y = (y0 + (sy as f32 + 0.5) * cell / steps as f32).floor();
+
+                    if x >= 0.0
+                        && y >= 0.0
+                        && (x as usize) < self.width
+                        && (y as usize) < self.height
+                    {
+                        sum += self.ink[y as usize * self.width + x as usize];
+                    }
+                    samples += 1;
+                }
+            }
+
+            *value = sum / samples as f32;
+        }
+
+        let mean = cells.iter().sum::<f32>() / cells.len() as f32;
+        for (i, value) in cells.iter().enumerate() {
+            if *value > mean {
+                hash[i / 64] |= 1 << (i % 64);
+            }
+        }
+
+        hash
+    }
+}
+
+/// Hashes an icon body, as rendered through the same pipeline as the previews.
+pub fn hash_body(
+    width: usize,
+    height: usize,
+    body: &str,
+    fontdb: &Database,
+) -> Result<IconHash, Box<dyn Error>> {
+    let svg = icon_svg_document(width, height, body, RENDER_SIZE);
+    let pixmap = render_svg(svg.as_bytes(), fontdb)?;
+
+    Ok(InkMap::from_pixmap(&pixmap).hash())
+}
+
+/// Hashes an SVG or raster image file on disk.
+pub fn hash_file(path: &Path, fontdb: &Database) -> Result<IconHash, Box<dyn Error>> {
+    let is_svg = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("svg"));
+
+    if is_svg {
+        let data = std::fs::read(path)?;
+        let tree = resvg::usvg::Tree::from_data(&data, &resvg::usvg::Options::default(), fontdb)?;
+
+        // Render the longest side at the same size as icons are rendered at.
+        let size = tree.size();
+        let scale = RENDER_SIZE as f32 / size.width().max(size.height());
+        let mut pixmap = Pixmap::new(
+            (size.width() * scale).ceil() as u32,
+            (size.height() * scale).ceil() as u32,
+        )
+        .ok_or("Could not create pixmap.")?;
+        resvg::render(
+            &tree,
+            resvg::tiny_skia::Transform::from_scale(scale, scale),
+            &mut pixmap.as_mut(),
+        );
+
+        Ok(InkMap::from_pixmap(&pixmap).hash())
+    } else {
+        let image = image::open(path)?.to_rgba8();
+
+        Ok(InkMap::from_rgba(
+            image.width() as usize,
+            image.height() as usize,
+            image.as_raw(),
+        )
+        .hash())
+    }
+}
+
+pub fn distance(a: &IconHash, b: &IconHash) -> u32 {
+    a.iter().zip(b).map(|(a, b)| (a ^ b).count_ones()).sum()
+}
+
+pub fn hash_to_hex(hash: &IconHash) -> String {
+    hash.iter().map(|part| format!("{:016x}", part)).collect()
+}
+
+pub fn hash_from_hex(hex: &str) -> Result<IconHash, Box<dyn Error>> {
+    if hex.len() != 64 {
+        return Err(format!("Invalid icon hash '{}'.", hex).into());
+    }
+
+    let mut hash = [0; 4];
+    for (i, part) in hash.iter_mut().enumerate() {
+        *part = u64::from_str_radix(&hex[i * 16..(i + 1) * 16], 16)?;
+    }
+
+    Ok(hash)
+}
+
+/// Finds the icons that look the most like the target, which is either an icon
+/// identifier or the path of an SVG or raster image file.
+pub fn find_similar(
+    target: &str,
+    count: usize,
+    collections_cache: &mut HashMap<String, IconCollection>,
+    fontdb: &Database,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let hashes = get_cached_hashes()?;
+
+    let path = Path::new(target);
+    let target_hash = if path.is_file() {
+        hash_file(path, fontdb)?
+    } else if let Some(hash) = hashes.get(target) {
+        *hash
+    } else {
+        let (width, height, body) = get_icon_xml(target, collections_cache, None)?;
+        hash_body(width, height, &body, fontdb)?
+    };
+
+    let mut distances: Vec<(u32, &String)> = hashes
+        .iter()
+        .filter(|(icon, _)| icon.as_str() != target)
+        .map(|(icon, hash)| (distance(&target_hash, hash), icon))
+        .collect();
+    distances.sort();
+
+    Ok(distances
+        .into_iter()
+        .take(count)
+        .map(|(_, icon)| icon.to_string())
+        .collect())
+}
@@ -0,0 +1,297 @@
+use std::{collections::HashMap, error::Error};
+
+use log::info;
+use serde_json::{Map, Value};
+
+use crate::{
+    cli::SpriteArgs,
+    enums::IconCollection,
+    export::{icon_filename, read_icon_ids},
+    files::{get_icon_xml, write_file},
+};
+
+/// Removes the whitespace between tags, which Iconify bodies sometimes contain.
+fn minify_body(body: &str) -> String {
+    let mut result = String::with_capacity(body.len());
+
+    for (i, part) in body.split('>').enumerate() {
+        if i > 0 {
+            result.push('>');
+        }
+        result.push_str(if part.trim_start().starts_with('<') {
+            part.trim_start()
+        } else {
+            part
+        });
+    }
+
+    result.trim().to_string()
+}
+
+/// Whether an attribute starts at a byte offset, rather than being the end of a
+/// longer name like `data-id`.
+fn starts_attribute(text: &str, i: usize) -> bool {
+    text[..i].ends_with(|c: char| c.is_whitespace() || c == ':')
+}
+
+/// Replaces the attributes that start with `from`, leaving longer names alone.
+fn replace_attribute(text: &str, from: &str, to: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut last = 0;
+
+    for (i, _) in text.match_indices(from) {
+        if starts_attribute(text, i) {
+            result.push_str(&text[last..i]);
+            result.push_str(to);
+            last = i + from.len();
+        }
+    }
+    result.push_str(&text[last..]);
+
+    result
+}
+
+/// Prefixes the ids defined in an icon body, and the references to them, so the
+/// gradients and masks of different symbols can't clash.
+fn prefix_ids(body: &str, prefix: &str) -> String {
+    let mut ids = Vec::new();
+    for quote in ['"', '\''] {
+        let attribute = format!("id={}", quote);
+        for (i, _) in body.match_indices(&attribute) {
+            let value = &body[i + attribute.len()..];
+            if let (true, Some(end)) = (starts_attribute(body, i), value.find(quote)) {
+                ids.push((quote, &value[..end]));
+            }
+        }
+    }
+
+    let mut result = body.to_string();
+    for (quote, id) in ids {
+        let prefixed = format!("{}-{}", prefix, id);
+
+        result = replace_attribute(
+            &result,
+            &format!("id={0}{1}{0}", quote, id),
+            &format!("id={0}{1}{0}", quote, prefixed),
+        );
+        for quote in ['"', '\''] {
+            result = replace_attribute(
+                &result,
+                &format!("href={0}#{1}{0}", quote, id),
+                &format!("href={0}#{1}{0}", quote, prefixed),
+            );
+        }
+        for reference in ["#{}", "'#{}'", "\"#{}\""] {
+            result = result.replace(
+                &format!("url({})", reference.replace("{}", id)),
+                &format!("url({})", reference.replace("{}", &prefixed)),
+            );
+        }
+    }
+
+    result
+}
+
+/// The symbols of a sprite, and the symbol id of each of the icons in it.
+#[derive(Default)]
+struct Sprite {
+    symbols: Vec<String>,
+    /// The symbol id of each distinct icon, by size and minified body.
+    symbol_ids: HashMap<(usize, usize, String), String>,
+    /// The icon each symbol was rendered for, by symbol id.
+    rendered: HashMap<String, String>,
+    manifest: Map<String, Value>,
+}
+
+impl Sprite {
+    /// Adds an icon, as a new symbol unless an icon that looks exactly the same
+    /// was added before. Different icons that would get the same symbol id are an
+    /// error.
+    fn add(
+        &mut self,
+        icon: &str,
+        (width, height, body): (usize, usize, String),
+        symbol_id_template: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let key = (width, height, minify_body(&body));
+
+        let symbol_id = match self.symbol_ids.get(&key) {
+            Some(symbol_id) => symbol_id.clone(),
+            None => {
+                let symbol_id = icon_filename(symbol_id_template, icon)?;
+                if let Some(other) = self.rendered.insert(symbol_id.clone(), icon.to_string()) {
+                    return Err(format!(
+                        "{} and {} would both get the symbol id '{}', pick a --symbol-id template that tells them apart.",
+                        other, icon, symbol_id
+                    )
+                    .into());
+                }
+                self.symbols.push(format!(
+                    r#"<symbol id="{}" viewBox="0 0 {} {}">{}</symbol>"#,
+                    symbol_id,
+                    width,
+                    height,
+                    prefix_ids(&key.2, &symbol_id)
+                ));
+                self.symbol_ids.insert(key, symbol_id.clone());
+                symbol_id
+            }
+        };
+
+        self.manifest
+            .insert(icon.to_string(), Value::String(symbol_id));
+
+        Ok(())
+    }
+
+    /// The sprite, hidden without `display:none`, which would keep the
+    /// gradients and masks of its symbols from rendering.
+    fn svg(&self) -> String {
+        format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="0" height="0" style="position:absolute">{}</svg>"#,
+            self.symbols.join("")
+        )
+    }
+}
+
+/// Writes a single SVG of `<symbol>` elements, one per distinct icon, and a JSON
+/// manifest that maps the icon identifiers to their symbol ids. Icons that look
+/// exactly the same share a symbol.
+pub fn sprite(
+    args: &SpriteArgs,
+    collections_cache: &mut HashMap<String, IconCollection>,
+) -> Result<(), Box<dyn Error>> {
+    let icons = read_icon_ids(&args.icons, args.input.as_deref())?;
+    let mut sprite = Sprite::default();
+
+    for icon in &icons {
+        let data = get_icon_xml(icon, collections_cache, None)
+            .map_err(|e| format!("Could not read {}: {}", icon, e))?;
+        sprite.add(icon, data, &args.symbol_id)?;
+    }
+
+    write_file(&args.out, sprite.svg().as_bytes())?;
+
+    let manifest_path = match &args.manifest {
+        Some(path) => path.clone(),
+        None => args.out.with_extension("json"),
+    };
+    write_file(
+        &manifest_path,
+        serde_json::to_string_pretty(&Value::Object(sprite.manifest))?.as_bytes(),
+    )?;
+
+    info!(
+        "Wrote {} symbols for {} icons to {}",
+        sprite.symbols.len(),
+        icons.len(),
+        args.out.display()
+    );
+    println!("{}", args.out.display());
+    println!("{}", manifest_path.display());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(body: &str) -> (usize, usize, String) {
+        (24, 24, body.to_string())
+    }
+
+    #[test]
+    fn builds_a_symbol_per_icon() {
+        let mut sprite = Sprite::default();
+        sprite
+            .add(
+                "mdi:home",
+                data("<path d=\"M1 1\"/>\n  <path d=\"M2 2\"/>"),
+                "{prefix}-{name}",
+            )
+            .unwrap();
+        sprite
+            .add(
+                "lucide:house",
+                (16, 24, "<path/>".to_string()),
+                "{prefix}-{name}",
+            )
+            .unwrap();
+
+        assert_eq!(
+            sprite.svg(),
+            concat!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="0" height="0" style="position:absolute">"#,
+                r#"<symbol id="mdi-home" viewBox="0 0 24 24"><path d="M1 1"/><path d="M2 2"/></symbol>"#,
+                r#"<symbol id="lucide-house" viewBox="0 0 16 24"><path/></symbol>"#,
+                "</svg>"
+            )
+        );
+        assert_eq!(sprite.manifest["mdi:home"], "mdi-home");
+        assert_eq!(sprite.manifest["lucide:house"], "lucide-house");
+    }
+
+    #[test]
+    fn identical_icons_share_a_symbol() {
+        let mut sprite = Sprite::default();
+        sprite.add("mdi:home", data("<path/>"), "{name}").unwrap();
+        sprite
+            .add("mdi:house", data(" <path/> "), "{name}")
+            .unwrap();
+        // The same body at another size is another symbol.
+        sprite
+            .add("mdi:home-small", (16, 16, "<path/>".to_string()), "{name}")
+            .unwrap();
+
+        assert_eq!(sprite.symbols.len(), 2);
+        assert_eq!(sprite.manifest["mdi:house"], "home");
+        assert_eq!(sprite.manifest["mdi:home-small"], "home-small");
+    }
+
+    #[test]
+    fn clashing_symbol_ids_are_an_error() {
+        let mut sprite = Sprite::default();
+        sprite
+            .add("mdi:home", data("<path d=\"M1 1\"/>"), "{name}")
+            .unwrap();
+
+        let error = sprite
+            .add("lucide:home", data("<path d=\"M2 2\"/>"), "{name}")
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "mdi:home and lucide:home would both get the symbol id 'home', pick a --symbol-id template that tells them apart."
+        );
+    }
+
+    #[test]
+    fn internal_ids_are_prefixed() {
+        let body = concat!(
+            r#"<defs><linearGradient id="a"><stop/></linearGradient><mask id='m'/></defs>"#,
+            r##"<path fill="url(#a)" mask="url(#m)" data-id="a"/><use xlink:href="#a"/><use href='#m'/>"##
+        );
+
+        assert_eq!(
+            prefix_ids(body, "mdi-home"),
+            concat!(
+                r#"<defs><linearGradient id="mdi-home-a"><stop/></linearGradient><mask id='mdi-home-m'/></defs>"#,
+                r##"<path fill="url(#mdi-home-a)" mask="url(#mdi-home-m)" data-id="a"/><use xlink:href="#mdi-home-a"/><use href='#mdi-home-m'/>"##
+            )
+        );
+    }
+
+    #[test]
+    fn symbols_get_their_own_ids() {
+        let body = r#"<defs><linearGradient id="a"/></defs><path fill="url(#a)"/>"#;
+        let mut sprite = Sprite::default();
+        sprite.add("logos:rust", data(body), "{name}").unwrap();
+        sprite
+            .add("logos:go", data(&body.replace("path", "circle")), "{name}")
+            .unwrap();
+
+        let svg = sprite.svg();
+        assert!(svg.contains(r#"<linearGradient id="rust-a"/></defs><path fill="url(#rust-a)"/>"#));
+        assert!(svg.contains(r#"<linearGradient id="go-a"/></defs><circle fill="url(#go-a)"/>"#));
+    }
+}
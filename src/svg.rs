@@ -0,0 +1,192 @@
+/// How an icon is turned into a standalone SVG document.
+#[derive(Clone, Debug)]
+pub struct SvgOptions {
+    /// The height of the document, e.g. `24` or `1.5em`. The width follows from
+    /// the aspect ratio of the icon. Defaults to the size of the icon itself.
+    pub size: Option<String>,
+    /// The colour of monochrome icons. `currentColor` inherits the text colour.
+    pub color: String,
+    /// Overrides the width of the strokes in stroke based icons.
+    pub stroke_width: Option<String>,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            size: None,
+            color: "currentColor".to_string(),
+            stroke_width: None,
+        }
+    }
+}
+
+/// Scales a dimension such as `24`, `1.5em` or `100%`, keeping its unit.
+pub fn scale_dimension(dimension: &str, ratio: f32) -> String {
+    let unit_start = dimension
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(dimension.len());
+    let (value, unit) = dimension.split_at(unit_start);
+
+    match value.parse::<f32>() {
+        Ok(value) => {
+            let scaled = (value * ratio * 1000.0).round() / 1000.0;
+            format!("{}{}", scaled, unit)
+        }
+        Err(_) => dimension.to_string(),
+    }
+}
+
+/// Replaces the values of an attribute throughout the body.
+fn replace_attribute(body: &str, name: &str, value: &str) -> String {
+    let attribute = format!(" {}=\"", name);
+    let mut result = String::with_capacity(body.len());
+    let mut rest = body;
+
+    while let Some(start) = rest.find(&attribute) {
+        let value_start = start + attribute.len();
+        let Some(value_end) = rest[value_start..].find('"') else {
+            break;
+        };
+
+        result.push_str(&rest[..value_start]);
+        result.push_str(value);
+        rest = &rest[value_start + value_end..];
+    }
+    result.push_str(rest);
+
+    result
+}
+
+/// Applies the colour and stroke width options to an icon body.
+pub fn style_body(body: &str, options: &SvgOptions) -> String {
+    let mut body = body.to_string();
+
+    if options.color != "currentColor" {
+        body = body.replace("currentColor", &options.color);
+    }
+
+    if let Some(stroke_width) = &options.stroke_width {
+        if body.contains(" stroke-width=\"") {
+            body = replace_attribute(&body, "stroke-width", stroke_width);
+        } else if body.contains(" stroke=\"") {
+            // Strokes without an explicit width inherit it from the group.
+            body = format!("<g stroke-width=\"{}\">{}</g>", stroke_width, body);
+        }
+    }
+
+    body
+}
+
+/// Builds a standalone SVG document for an icon body.
+pub fn standalone_svg(width: usize, height: usize, body: &str, options: &SvgOptions) -> String {
+    let (document_width, document_height) = match &options.size {
+        Some(size) => (
+            scale_dimension(size, width as f32 / height as f32),
+            size.to_string(),
+        ),
+        None => (width.to_string(), height.to_string()),
+    };
+
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">{}</svg>"#,
+        document_width,
+        document_height,
+        width,
+        height,
+        style_body(body, options)
+    )
+}
+
+/// Encodes an SVG document as a data URI for CSS. Double quotes become single
+/// quotes and only the characters that break URLs are percent-encoded, which keeps
+/// the URI much shorter than base64.
+pub fn svg_data_uri(svg: &str) -> String {
+    let mut result = String::from("data:image/svg+xml,");
+
+    for c in svg.split_whitespace().collect::<Vec<_>>().join(" ").chars() {
+        match c {
+            '"' => result.push('\''),
+            '%' | '#' | '<' | '>' | '{' | '}' | '\'' => {
+                result.push_str(&format!("%{:02X}", c as u32));
+            }
+            c if c.is_ascii() && !c.is_ascii_control() => result.push(c),
+            c => {
+                let mut bytes = [0; 4];
+                for byte in c.encode_utf8(&mut bytes).bytes() {
+                    result.push_str(&format!("%{:02X}", byte));
+                }
+            }
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dimensions_keep_their_unit() {
+        assert_eq!(scale_dimension("24", 1.0), "24");
+        assert_eq!(scale_dimension("24", 0.5), "12");
+        assert_eq!(scale_dimension("1.5em", 2.0), "3em");
+        assert_eq!(scale_dimension("100%", 1.0 / 3.0), "33.333%");
+        assert_eq!(scale_dimension("auto", 2.0), "auto");
+    }
+
+    #[test]
+    fn documents_are_sized_by_height() {
+        let options = SvgOptions {
+            size: Some("2em".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            standalone_svg(32, 16, "<path/>", &options),
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="4em" height="2em" viewBox="0 0 32 16"><path/></svg>"#
+        );
+        assert_eq!(
+            standalone_svg(24, 24, "<path/>", &SvgOptions::default()),
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="24" height="24" viewBox="0 0 24 24"><path/></svg>"#
+        );
+    }
+
+    #[test]
+    fn colors_replace_current_color() {
+        let options = SvgOptions {
+            color: "#f00".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            style_body(r#"<path fill="currentColor"/>"#, &options),
+            r##"<path fill="#f00"/>"##
+        );
+        assert_eq!(
+            style_body(r#"<path fill="currentColor"/>"#, &SvgOptions::default()),
+            r#"<path fill="currentColor"/>"#
+        );
+    }
+
+    #[test]
+    fn stroke_widths_are_replaced_or_added() {
+        let options = SvgOptions {
+            stroke_width: Some("1.5".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            style_body(
+                r#"<path stroke="currentColor" stroke-width="2" data-stroke-width="2"/>"#,
+                &options
+            ),
+            r#"<path stroke="currentColor" stroke-width="1.5" data-stroke-width="2"/>"#
+        );
+        assert_eq!(
+            style_body(r#"<path stroke="currentColor"/>"#, &options),
+            r#"<g stroke-width="1.5"><path stroke="currentColor"/></g>"#
+        );
+        assert_eq!(style_body("<path/>", &options), "<path/>");
+    }
+}
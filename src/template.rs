@@ -0,0 +1,327 @@
+use std::{collections::HashMap, error::Error};
+
+use crate::{
+    enums::IconCollection,
+    files::get_icon_xml,
+    svg::{standalone_svg, svg_data_uri, SvgOptions},
+};
+
+/// The built-in templates for common icon libraries, by name. Templates of the
+/// same name in the config override them.
+pub const PRESETS: [(&str, &str); 6] = [
+    ("iconify-react", r#"<Icon icon="{icon}" />"#),
+    (
+        "unplugin-icons",
+        "import {prefix|pascal}{name|pascal} from '~icons/{prefix}/{name}'",
+    ),
+    ("astro-icon", r#"<Icon name="{icon}" />"#),
+    ("tailwind", "icon-[{prefix}--{name}]"),
+    ("unocss", "i-{prefix}-{name}"),
+    (
+        "iconify-icon",
+        r#"<iconify-icon icon="{icon}"></iconify-icon>"#,
+    ),
+];
+
+/// Returns the built-in template of the given name.
+pub fn preset(name: &str) -> Option<&'static str> {
+    PRESETS
+        .iter()
+        .find(|(preset, _)| *preset == name)
+        .map(|(_, template)| *template)
+}
+
+/// The filters that change the case of a placeholder value, e.g. `{name|pascal}`.
+const FILTERS: [&str; 6] = ["pascal", "camel", "snake", "kebab", "upper", "lower"];
+
+/// Splits a value such as `arrow-left` or `mdi:home` into its words.
+fn words(value: &str) -> Vec<&str> {
+    value
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+
+    match chars.next() {
+        Some(first) => first.to_uppercase().collect::<String>() + &chars.as_str().to_lowercase(),
+        None => String::new(),
+    }
+}
+
+pub fn apply_filter(value: &str, filter: &str) -> Result<String, Box<dyn Error>> {
+    let words = words(value);
+
+    Ok(match filter {
+        "pascal" => words.iter().map(|word| capitalize(word)).collect(),
+        "camel" => words
+            .iter()
+            .enumerate()
+            .map(|(i, word)| {
+                if i == 0 {
+                    word.to_lowercase()
+                } else {
+                    capitalize(word)
+                }
+            })
+            .collect(),
+        "snake" => words.join("_").to_lowercase(),
+        "kebab" => words.join("-").to_lowercase(),
+        "upper" => value.to_uppercase(),
+        "lower" => value.to_lowercase(),
+        _ => {
+            return Err(format!(
+                "Unknown template filter '{}', expected one of {}.",
+                filter,
+                FILTERS.join(", ")
+            )
+            .into())
+        }
+    })
+}
+
+/// The values of the placeholders for an icon. The icon itself and its collection
+/// are only read when a placeholder needs them.
+struct TemplateValues<'a> {
+    icon: &'a str,
+    prefix: &'a str,
+    name: &'a str,
+    api: Option<&'a str>,
+    data: Option<(usize, usize, String)>,
+}
+
+impl TemplateValues<'_> {
+    fn data(
+        &mut self,
+        collections_cache: &mut HashMap<String, IconCollection>,
+    ) -> Result<&(usize, usize, String), Box<dyn Error>> {
+        let data = match self.data.take() {
+            Some(data) => data,
+            None => get_icon_xml(self.icon, collections_cache, self.api)?,
+        };
+
+        Ok(self.data.insert(data))
+    }
+
+    fn svg(
+        &mut self,
+        collections_cache: &mut HashMap<String, IconCollection>,
+    ) -> Result<String, Box<dyn Error>> {
+        let (width, height, body) = self.data(collections_cache)?;
+
+        Ok(standalone_svg(
+            *width,
+            *height,
+            body,
+            &SvgOptions::default(),
+        ))
+    }
+
+    fn collection<'c>(
+        &mut self,
+        collections_cache: &'c mut HashMap<String, IconCollection>,
+    ) -> Result<&'c IconCollection, Box<dyn Error>> {
+        self.data(collections_cache)?;
+
+        Ok(&collections_cache[self.prefix])
+    }
+
+    /// The value of a placeholder, or None if there is no such placeholder.
+    fn get(
+        &mut self,
+        key: &str,
+        collections_cache: &mut HashMap<String, IconCollection>,
+    ) -> Result<Option<String>, Box<dyn Error>> {
+        Ok(Some(match key {
+            "icon" => self.icon.to_string(),
+            "prefix" => self.prefix.to_string(),
+            "name" => self.name.to_string(),
+            "svg" => self.svg(collections_cache)?,
+            "body" => self.data(collections_cache)?.2.clone(),
+            "data_uri" => svg_data_uri(&self.svg(collections_cache)?),
+            "width" => self.data(collections_cache)?.0.to_string(),
+            "height" => self.data(collections_cache)?.1.to_string(),
+            "collection" => self.collection(collections_cache)?.info.name.clone(),
+            "license" => self
+                .collection(collections_cache)?
+                .info
+                .license
+                .title
+                .clone(),
+            "author" => self.collection(collections_cache)?.info.author.name.clone(),
+            _ => return Ok(None),
+        }))
+    }
+
+    /// Renders a placeholder such as `name|pascal`, or None if it isn't one.
+    fn render(
+        &mut self,
+        placeholder: &str,
+        collections_cache: &mut HashMap<String, IconCollection>,
+    ) -> Result<Option<String>, Box<dyn Error>> {
+        let mut parts = placeholder.split('|');
+
+        let Some(mut value) =
+            self.get(parts.next().unwrap_or_default().trim(), collections_cache)?
+        else {
+            return Ok(None);
+        };
+
+        for filter in parts {
+            value = apply_filter(&value, filter.trim())?;
+        }
+
+        Ok(Some(value))
+    }
+}
+
+/// Fills in the placeholders of a template for an icon, e.g. `{icon}` or
+/// `{name|pascal}`. Braces that don't hold a known placeholder are kept as they
+/// are, so templates can contain code like `{...props}`.
+pub fn render_template(
+    template: &str,
+    icon: &str,
+    collections_cache: &mut HashMap<String, IconCollection>,
+    api: Option<&str>,
+) -> Result<String, Box<dyn Error>> {
+    let (prefix, name) = icon
+        .split_once(':')
+        .ok_or(format!("Invalid icon identifier '{}'.", icon))?;
+
+    let mut values = TemplateValues {
+        icon,
+        prefix,
+        name,
+        api,
+        data: None,
+    };
+
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+
+        // A placeholder ends at the next closing brace, unless another one opens first.
+        let end = rest
+            .find(['{', '}'])
+            .filter(|&end| rest[end..].starts_with('}'));
+
+        let value = match end {
+            Some(end) => values.render(&rest[..end], collections_cache)?,
+            None => None,
+        };
+
+        match (end, value) {
+            (Some(end), Some(value)) => {
+                result.push_str(&value);
+                rest = &rest[end + 1..];
+            }
+            _ => result.push('{'),
+        }
+    }
+    result.push_str(rest);
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(template: &str) -> Result<String, Box<dyn Error>> {
+        render_template(template, "mdi:arrow-left", &mut HashMap::new(), None)
+    }
+
+    #[test]
+    fn filters_change_the_case() {
+        let cases = [
+            ("pascal", "ArrowLeft"),
+            ("camel", "arrowLeft"),
+            ("snake", "arrow_left"),
+            ("kebab", "arrow-left"),
+            ("upper", "ARROW-LEFT"),
+            ("lower", "arrow-left"),
+        ];
+
+        for (filter, expected) in cases {
+            assert_eq!(apply_filter("Arrow-left", filter).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn filters_split_identifiers_into_words() {
+        assert_eq!(
+            apply_filter("mdi:arrow-left", "pascal").unwrap(),
+            "MdiArrowLeft"
+        );
+        assert_eq!(
+            apply_filter("mdi:arrow-left", "snake").unwrap(),
+            "mdi_arrow_left"
+        );
+    }
+
+    #[test]
+    fn placeholders_are_filled_in() {
+        assert_eq!(
+            render("import {prefix|pascal}{name|pascal} from '~icons/{prefix}/{name}'").unwrap(),
+            "import MdiArrowLeft from '~icons/mdi/arrow-left'"
+        );
+        assert_eq!(render("{icon|upper}").unwrap(), "MDI:ARROW-LEFT");
+    }
+
+    #[test]
+    fn icon_placeholders_read_the_cached_collection() {
+        let collection: IconCollection = serde_json::from_value(serde_json::json!({
+            "prefix": "mdi",
+            "width": 24,
+            "height": 24,
+            "lastModified": 0,
+            "info": {
+                "name": "Material Design Icons",
+                "total": 1,
+                "author": { "name": "Pictogrammers" },
+                "license": { "title": "Apache 2.0", "spdx": "Apache-2.0" },
+                "samples": [],
+                "palette": false
+            },
+            "icons": { "arrow-left": { "body": "<path d=\"M20 11v2H8\"/>" } }
+        }))
+        .unwrap();
+        let mut collections_cache = HashMap::from([("mdi".to_string(), collection)]);
+
+        assert_eq!(
+            render_template(
+                "{width}x{height} {body} ({collection}, {license}, {author})",
+                "mdi:arrow-left",
+                &mut collections_cache,
+                None
+            )
+            .unwrap(),
+            "24x24 <path d=\"M20 11v2H8\"/> (Material Design Icons, Apache 2.0, Pictogrammers)"
+        );
+    }
+
+    #[test]
+    fn unknown_placeholders_are_kept() {
+        assert_eq!(
+            render("<Icon {...props} icon=\"{icon}\" {size} />").unwrap(),
+            "<Icon {...props} icon=\"mdi:arrow-left\" {size} />"
+        );
+        assert_eq!(render("{{name}}").unwrap(), "{arrow-left}");
+        assert_eq!(render("{name").unwrap(), "{name");
+    }
+
+    #[test]
+    fn unknown_filters_are_an_error() {
+        let error = render("{name|shout}").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unknown template filter 'shout', expected one of pascal, camel, snake, kebab, upper, lower."
+        );
+        assert!(apply_filter("home", "").is_err());
+    }
+}
@@ -0,0 +1,227 @@
+use std::{collections::HashSet, fmt::Display, str::FromStr};
+
+/// The drawing characteristics of an icon, found by analysing its SVG body.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IconTraits {
+    /// The icon is drawn with strokes, so the line width can be changed.
+    pub stroke: bool,
+    /// The icon is drawn with filled shapes.
+    pub fill: bool,
+    /// The icon uses a single colour, so it can be recoloured.
+    pub mono: bool,
+    /// The icon contains SMIL animations.
+    pub animated: bool,
+}
+
+/// The keys that can be used as filters in queries, e.g. `style:stroke`.
+pub const FILTER_KEYS: [&str; 3] = ["style", "color", "animated"];
+
+/// The values a filter key accepts.
+pub fn filter_values(key: &str) -> &'static [&'static str] {
+    match key {
+        "style" => &["stroke", "fill"],
+        "color" => &["mono", "multi"],
+        "animated" => &["true", "false"],
+        _ => &[],
+    }
+}
+
+/// Returns the values of an attribute in the body, including values set
+/// through the `style` attribute.
+fn attribute_values<'a>(body: &'a str, name: &str) -> Vec<&'a str> {
+    let mut values = Vec::new();
+
+    let attribute = format!("{}=\"", name);
+    for (i, _) in body.match_indices(&attribute) {
+        let preceded_by_whitespace = body[..i].ends_with(char::is_whitespace);
+        let value_start = i + attribute.len();
+
+        if let (true, Some(value_end)) = (preceded_by_whitespace, body[value_start..].find('"')) {
+            values.push(body[value_start..value_start + value_end].trim());
+        }
+    }
+
+    for (i, _) in body.match_indices("style=\"") {
+        let style_start = i + "style=\"".len();
+        let Some(style_end) = body[style_start..].find('"') else {
+            continue;
+        };
+
+        for declaration in body[style_start..style_start + style_end].split(';') {
+            if let Some((property, value)) = declaration.split_once(':') {
+                if property.trim() == name {
+                    values.push(value.trim());
+                }
+            }
+        }
+    }
+
+    values
+}
+
+fn is_paint(value: &str) -> bool {
+    !matches!(value, "none" | "transparent" | "")
+}
+
+impl IconTraits {
+    pub fn from_body(body: &str) -> IconTraits {
+        let fills = attribute_values(body, "fill");
+        let strokes = attribute_values(body, "stroke");
+
+        // Shapes are filled with black by default, so only an explicit `none` disables filling.
+        let fill = fills.is_empty() || fills.iter().any(|value| is_paint(value));
+        let stroke = strokes.iter().any(|value| is_paint(value));
+
+        let mut colors = HashSet::new();
+        for name in ["fill", "stroke", "stop-color"] {
+            for value in attribute_values(body, name) {
+                if is_paint(value) {
+                    colors.insert(value.to_lowercase());
+                }
+            }
+        }
+
+        IconTraits {
+            stroke,
+            fill,
+            mono: colors.len() <= 1,
+            animated: body.contains("<animate") || body.contains("<set "),
+        }
+    }
+
+    /// Checks if the traits match a filter such as `style:stroke` or `color:mono`.
+    pub fn matches(&self, key: &str, value: &str) -> bool {
+        match (key, value) {
+            ("style", "stroke") => self.stroke,
+            ("style", "fill") => self.fill,
+            ("color", "mono") => self.mono,
+            ("color", "multi") => !self.mono,
+            ("animated", "true") => self.animated,
+            ("animated", "false") => !self.animated,
+            _ => false,
+        }
+    }
+}
+
+impl Display for IconTraits {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut traits = Vec::new();
+
+        if self.stroke {
+            traits.push("stroke");
+        }
+        if self.fill {
+            traits.push("fill");
+        }
+        if self.mono {
+            traits.push("mono");
+        }
+        if self.animated {
+            traits.push("animated");
+        }
+
+        write!(f, "{}", traits.join(","))
+    }
+}
+
+impl FromStr for IconTraits {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut traits = IconTraits::default();
+
+        for t in s.split(',').filter(|t| !t.is_empty()) {
+            match t {
+                "stroke" => traits.stroke = true,
+                "fill" => traits.fill = true,
+                "mono" => traits.mono = true,
+                "animated" => traits.animated = true,
+                _ => return Err(format!("Unknown icon trait '{}'.", t)),
+            }
+        }
+
+        Ok(traits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shapes_are_filled_by_default() {
+        let traits = IconTraits::from_body(r#"<path d="M0 0h24v24H0z"/>"#);
+
+        assert!(traits.fill);
+        assert!(!traits.stroke);
+        assert!(traits.mono);
+    }
+
+    #[test]
+    fn stroke_icons_without_fill() {
+        let traits = IconTraits::from_body(
+            r#"<g fill="none" stroke="currentColor" stroke-width="2"><path d="M5 12h14"/></g>"#,
+        );
+
+        assert!(traits.stroke);
+        assert!(!traits.fill);
+        assert!(traits.mono);
+    }
+
+    #[test]
+    fn paint_in_style_attributes() {
+        let traits = IconTraits::from_body(r#"<path style="fill: none; stroke: #000" d="M0 0"/>"#);
+
+        assert!(traits.stroke);
+        assert!(!traits.fill);
+    }
+
+    #[test]
+    fn attribute_names_must_match_whole() {
+        // `stroke-width` and `data-fill` are not paint.
+        let traits = IconTraits::from_body(r#"<path data-fill="red" stroke-width="2" d="M0 0"/>"#);
+
+        assert!(traits.fill);
+        assert!(!traits.stroke);
+        assert!(traits.mono);
+    }
+
+    #[test]
+    fn counts_colors_case_insensitively() {
+        let mono =
+            IconTraits::from_body(r##"<path fill="#FFF" d="M0 0"/><path fill="#fff" d="M1 1"/>"##);
+        let multi = IconTraits::from_body(
+            r##"<path fill="#f00" d="M0 0"/><stop stop-color="#00f"/><path stroke="none" d="M1 1"/>"##,
+        );
+
+        assert!(mono.mono);
+        assert!(!multi.mono);
+    }
+
+    #[test]
+    fn finds_animations() {
+        assert!(
+            IconTraits::from_body(r#"<circle r="2"><animate attributeName="r"/></circle>"#)
+                .animated
+        );
+        assert!(
+            IconTraits::from_body(r#"<circle r="2"><set attributeName="r" to="3"/></circle>"#)
+                .animated
+        );
+        assert!(!IconTraits::from_body(r#"<circle r="2"/>"#).animated);
+    }
+
+    #[test]
+    fn round_trips_through_strings() {
+        let traits = IconTraits {
+            stroke: true,
+            fill: false,
+            mono: true,
+            animated: true,
+        };
+
+        assert_eq!(traits.to_string(), "stroke,mono,animated");
+        assert_eq!(traits.to_string().parse::<IconTraits>().unwrap(), traits);
+        assert!("glow".parse::<IconTraits>().is_err());
+    }
+}